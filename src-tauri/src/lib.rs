@@ -1,13 +1,14 @@
-use chrono::{Datelike, Local, TimeZone, Utc};
+use chrono::{Datelike, FixedOffset, Local, Months, NaiveDate, Offset, TimeZone, Timelike, Utc};
 use httparse::Status;
 use rand::{distributions::Alphanumeric, Rng};
 use rusqlite::{params, types::Value, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     fs,
     io::{Read, Write},
     net::{TcpListener, UdpSocket},
+    path::{Path, PathBuf},
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -28,11 +29,13 @@ const TIMER_STATE_KEY: &str = "timer_state";
 const TRAY_ID: &str = "pomodoro-tray";
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "snake_case")]
+#[serde(into = "String", try_from = "String")]
 enum TimerPhase {
     Focus,
     ShortBreak,
     LongBreak,
+    /// User-defined phase kind from a cycle plan, keyed by its slug (e.g. `review`).
+    Custom(String),
 }
 
 impl std::fmt::Display for TimerPhase {
@@ -41,17 +44,26 @@ impl std::fmt::Display for TimerPhase {
             TimerPhase::Focus => "Focus",
             TimerPhase::ShortBreak => "Short break",
             TimerPhase::LongBreak => "Long break",
+            TimerPhase::Custom(kind) => {
+                let words = kind.replace(['_', '-'], " ");
+                let mut chars = words.chars();
+                return match chars.next() {
+                    Some(first) => write!(f, "{}{}", first.to_ascii_uppercase(), chars.as_str()),
+                    None => Ok(()),
+                };
+            }
         };
         write!(f, "{label}")
     }
 }
 
 impl TimerPhase {
-    fn as_db_value(&self) -> &'static str {
+    fn as_db_value(&self) -> &str {
         match self {
             TimerPhase::Focus => "focus",
             TimerPhase::ShortBreak => "short_break",
             TimerPhase::LongBreak => "long_break",
+            TimerPhase::Custom(kind) => kind,
         }
     }
 
@@ -60,11 +72,34 @@ impl TimerPhase {
             "focus" => Ok(TimerPhase::Focus),
             "short_break" => Ok(TimerPhase::ShortBreak),
             "long_break" => Ok(TimerPhase::LongBreak),
+            other if is_valid_phase_kind(other) => Ok(TimerPhase::Custom(other.to_string())),
             other => Err(format!("unknown timer phase: {other}")),
         }
     }
 }
 
+impl From<TimerPhase> for String {
+    fn from(phase: TimerPhase) -> Self {
+        phase.as_db_value().to_string()
+    }
+}
+
+impl TryFrom<String> for TimerPhase {
+    type Error = String;
+
+    fn try_from(value: String) -> AppResult<Self> {
+        TimerPhase::from_db_value(&value)
+    }
+}
+
+fn is_valid_phase_kind(kind: &str) -> bool {
+    !kind.is_empty()
+        && kind.len() <= 32
+        && kind
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", default)]
 struct AppSettings {
@@ -78,6 +113,22 @@ struct AppSettings {
     remote_control_enabled: bool,
     remote_control_port: i64,
     remote_control_token: String,
+    auto_start_breaks: bool,
+    auto_start_focus: bool,
+    auto_start_delay_sec: i64,
+    // Number of focus phases after which auto-start stops; 0 means no limit.
+    auto_stop_after_cycles: i64,
+    // Let focus phases keep counting up past zero instead of ending.
+    flow_mode_enabled: bool,
+    // Days deleted items stay in the trash before being purged; 0 keeps them until emptied.
+    trash_retention_days: i64,
+    // Local "HH:MM" at which one analytics day ends and the next begins.
+    day_starts_at: String,
+    // Completed pomodoros a day needs to count toward a streak; 0 means any focus counts.
+    daily_goal_pomodoros: i64,
+    // Weekdays (0 = Monday) and `YYYY-MM-DD` dates that never break a streak.
+    rest_weekdays: Vec<u32>,
+    vacation_dates: Vec<String>,
 }
 
 impl Default for AppSettings {
@@ -93,6 +144,16 @@ impl Default for AppSettings {
             remote_control_enabled: false,
             remote_control_port: 48484,
             remote_control_token: String::new(),
+            auto_start_breaks: false,
+            auto_start_focus: false,
+            auto_start_delay_sec: 0,
+            auto_stop_after_cycles: 0,
+            flow_mode_enabled: false,
+            trash_retention_days: 30,
+            day_starts_at: "00:00".to_string(),
+            daily_goal_pomodoros: 0,
+            rest_weekdays: Vec::new(),
+            vacation_dates: Vec::new(),
         }
     }
 }
@@ -103,8 +164,14 @@ impl AppSettings {
             TimerPhase::Focus => self.focus_min * 60,
             TimerPhase::ShortBreak => self.short_break_min * 60,
             TimerPhase::LongBreak => self.long_break_min * 60,
+            // Custom kinds only exist inside cycle plans, which carry their own durations.
+            TimerPhase::Custom(_) => self.focus_min * 60,
         }
     }
+
+    fn day_start_minutes(&self) -> i64 {
+        parse_day_start(&self.day_starts_at).unwrap_or(0)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -120,6 +187,16 @@ struct AppSettingsPatch {
     remote_control_enabled: Option<bool>,
     remote_control_port: Option<i64>,
     remote_control_token: Option<String>,
+    auto_start_breaks: Option<bool>,
+    auto_start_focus: Option<bool>,
+    auto_start_delay_sec: Option<i64>,
+    auto_stop_after_cycles: Option<i64>,
+    flow_mode_enabled: Option<bool>,
+    trash_retention_days: Option<i64>,
+    day_starts_at: Option<String>,
+    daily_goal_pomodoros: Option<i64>,
+    rest_weekdays: Option<Vec<u32>>,
+    vacation_dates: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -134,15 +211,138 @@ struct TimerState {
     interruptions: i64,
     current_project_id: Option<i64>,
     current_tag_ids: Vec<i64>,
+    #[serde(default)]
+    current_task_id: Option<i64>,
+    // Day-plan item whose pomodoros the current focus counts towards.
+    #[serde(default)]
+    day_plan_item_id: Option<i64>,
     target_ends_at: Option<i64>,
+    #[serde(default)]
+    phase_label: String,
+    #[serde(default)]
+    counts_as_focus: bool,
+    // Selected cycle plan and the index of the current step within it.
+    // `None` means the built-in Focus / Short break / Long break cycle.
+    #[serde(default)]
+    plan_id: Option<i64>,
+    #[serde(default)]
+    plan_step: i64,
+    #[serde(default)]
+    profile_id: Option<i64>,
+    // Set while waiting out the grace period before an auto-started phase.
+    #[serde(default)]
+    auto_start_at: Option<i64>,
+    // Focus phases completed since the timer was last started by hand.
+    #[serde(default)]
+    auto_run_cycles: i64,
+    // Flow mode: the focus phase reached zero and keeps running as overtime.
+    #[serde(default)]
+    in_overtime: bool,
+    #[serde(default)]
+    overtime_seconds: i64,
+    // Net seconds added to (or taken from) the current phase via `timer_adjust`.
+    #[serde(default)]
+    adjusted_seconds: i64,
+    // Pauses within the current phase; written to `session_pauses` when it is recorded.
+    #[serde(default)]
+    pauses: Vec<SessionPause>,
+    // Reasoned pauses and parking-lot notes for the current phase, flushed with the session.
+    #[serde(default)]
+    interruption_log: Vec<InterruptionEntry>,
+    // What the current phase is about; becomes the recorded session's note.
+    #[serde(default)]
+    session_note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+enum InterruptionKind {
+    Internal,
+    External,
+}
+
+impl InterruptionKind {
+    fn as_db_value(&self) -> &'static str {
+        match self {
+            InterruptionKind::Internal => "internal",
+            InterruptionKind::External => "external",
+        }
+    }
+
+    fn from_db_value(value: &str) -> AppResult<Self> {
+        match value {
+            "internal" => Ok(InterruptionKind::Internal),
+            "external" => Ok(InterruptionKind::External),
+            other => Err(format!("unknown interruption kind: {other}")),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct InterruptionReason {
+    kind: Option<InterruptionKind>,
+    note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct InterruptionEntry {
+    occurred_at: i64,
+    kind: Option<InterruptionKind>,
+    note: Option<String>,
+    // `false` for parking-lot notes taken without pausing.
+    paused: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InterruptionReasonStat {
+    kind: Option<InterruptionKind>,
+    note: Option<String>,
+    pauses: i64,
+    distractions: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct RatingBucket {
+    key: String,
+    label: String,
+    sessions: i64,
+    avg_focus_rating: f64,
+    // `None` when no session in the bucket has an energy level.
+    avg_energy_level: Option<f64>,
+}
+
+/// Rated focus sessions grouped several ways, plus Pearson coefficients of the rating
+/// against interruption count and session length.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct RatingCorrelations {
+    rated_sessions: i64,
+    by_hour: Vec<RatingBucket>,
+    by_project: Vec<RatingBucket>,
+    by_interruptions: Vec<RatingBucket>,
+    by_length: Vec<RatingBucket>,
+    interruptions_correlation: Option<f64>,
+    length_correlation: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct SessionPause {
+    paused_at: i64,
+    // `None` when the phase ended while still paused.
+    resumed_at: Option<i64>,
 }
 
 impl TimerState {
     fn default_with_settings(settings: &AppSettings) -> Self {
-        let phase = TimerPhase::Focus;
-        let phase_total_seconds = settings.duration_for_phase_seconds(&phase);
+        let step = CyclePlanStep::builtin(TimerPhase::Focus, settings);
+        let phase_total_seconds = step.duration_min * 60;
         Self {
-            phase,
+            phase: step.phase,
             remaining_seconds: phase_total_seconds,
             is_running: false,
             cycle_index: 0,
@@ -151,11 +351,86 @@ impl TimerState {
             interruptions: 0,
             current_project_id: None,
             current_tag_ids: Vec::new(),
+            current_task_id: None,
+            day_plan_item_id: None,
             target_ends_at: None,
+            phase_label: step.label,
+            counts_as_focus: step.counts_as_focus,
+            plan_id: None,
+            plan_step: 0,
+            profile_id: None,
+            auto_start_at: None,
+            auto_run_cycles: 0,
+            in_overtime: false,
+            overtime_seconds: 0,
+            adjusted_seconds: 0,
+            pauses: Vec::new(),
+            interruption_log: Vec::new(),
+            session_note: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct TimerProfile {
+    id: i64,
+    name: String,
+    focus_min: i64,
+    short_break_min: i64,
+    long_break_min: i64,
+    long_break_every: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TimerProfileInput {
+    id: Option<i64>,
+    name: String,
+    focus_min: i64,
+    short_break_min: i64,
+    long_break_min: i64,
+    long_break_every: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct CyclePlanStep {
+    phase: TimerPhase,
+    #[serde(default)]
+    label: String,
+    duration_min: i64,
+    #[serde(default)]
+    counts_as_focus: bool,
+}
+
+impl CyclePlanStep {
+    fn builtin(phase: TimerPhase, settings: &AppSettings) -> Self {
+        Self {
+            label: phase.to_string(),
+            duration_min: settings.duration_for_phase_seconds(&phase) / 60,
+            counts_as_focus: phase == TimerPhase::Focus,
+            phase,
         }
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct CyclePlan {
+    id: i64,
+    name: String,
+    steps: Vec<CyclePlanStep>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CyclePlanInput {
+    id: Option<i64>,
+    name: String,
+    steps: Vec<CyclePlanStep>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct StartTimerRequest {
@@ -165,6 +440,27 @@ struct StartTimerRequest {
     // - field present as number: set current project (Some(Some(id)))
     project_id: Option<Option<i64>>,
     tag_ids: Option<Vec<i64>>,
+    // Missing leaves the note as is, `null` clears it.
+    #[serde(default, deserialize_with = "deserialize_present")]
+    note: Option<Option<String>>,
+    // Selecting a task also selects its project unless `project_id` is given too.
+    #[serde(default, deserialize_with = "deserialize_present")]
+    task_id: Option<Option<i64>>,
+}
+
+/// Deserializes a field that is present (even as `null`) as `Some`, leaving missing fields `None`.
+fn deserialize_present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AdjustTimerRequest {
+    delta_seconds: i64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -178,6 +474,113 @@ struct CompleteSessionRequest {
     interruptions: i64,
     project_id: Option<i64>,
     tag_ids: Option<Vec<i64>>,
+    // Defaults to `true` for focus and `false` for every other phase.
+    counts_as_focus: Option<bool>,
+    profile_id: Option<i64>,
+    // Both default to the plain `duration_sec` with no overtime.
+    planned_duration_sec: Option<i64>,
+    overtime_sec: Option<i64>,
+    adjusted_sec: Option<i64>,
+    note: Option<String>,
+    task_id: Option<i64>,
+    // What to do with existing sessions that overlap this one; keeps them by default.
+    #[serde(default)]
+    overlap: OverlapPolicy,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum OverlapPolicy {
+    /// Logs the session next to overlapping ones, as before overlaps were checked.
+    #[default]
+    Accept,
+    Reject,
+    /// Shortens overlapping sessions so they end before or start after the new one.
+    /// Rejects the session when an existing one surrounds it, since that would need a split.
+    Trim,
+    /// Deletes overlapping sessions.
+    Replace,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SessionErrorCode {
+    InvalidField,
+    NotFound,
+    Overlap,
+    Database,
+}
+
+/// Error returned by the session editing commands, shaped for the frontend to point at a field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionError {
+    code: SessionErrorCode,
+    field: Option<String>,
+    message: String,
+    conflicting_session_ids: Vec<i64>,
+}
+
+impl SessionError {
+    fn invalid(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            code: SessionErrorCode::InvalidField,
+            field: Some(field.to_string()),
+            message: message.into(),
+            conflicting_session_ids: Vec::new(),
+        }
+    }
+
+    fn not_found(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            code: SessionErrorCode::NotFound,
+            ..Self::invalid(field, message)
+        }
+    }
+
+    fn overlap(conflicting_session_ids: Vec<i64>) -> Self {
+        Self {
+            code: SessionErrorCode::Overlap,
+            message: format!(
+                "session overlaps {} existing session(s)",
+                conflicting_session_ids.len()
+            ),
+            conflicting_session_ids,
+            ..Self::invalid("startedAt", "")
+        }
+    }
+}
+
+impl From<String> for SessionError {
+    fn from(message: String) -> Self {
+        Self {
+            code: SessionErrorCode::Database,
+            field: None,
+            message,
+            conflicting_session_ids: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateSessionRequest {
+    id: i64,
+    started_at: Option<i64>,
+    ended_at: Option<i64>,
+    phase: Option<TimerPhase>,
+    // Defaults to the old duration shifted by the change in wall span.
+    duration_sec: Option<i64>,
+    completed: Option<bool>,
+    // Defaults to `true` for focus when the phase changes.
+    counts_as_focus: Option<bool>,
+    #[serde(default, deserialize_with = "deserialize_present")]
+    project_id: Option<Option<i64>>,
+    tag_ids: Option<Vec<i64>>,
+    #[serde(default, deserialize_with = "deserialize_present")]
+    note: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_present")]
+    task_id: Option<Option<i64>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -192,15 +595,35 @@ struct SessionRecord {
     interruptions: i64,
     project_id: Option<i64>,
     tag_ids: Vec<i64>,
+    counts_as_focus: bool,
+    profile_id: Option<i64>,
+    planned_duration_sec: i64,
+    overtime_sec: i64,
+    // Net seconds the phase was extended (positive) or shortened (negative) while running.
+    adjusted_sec: i64,
+    pauses: Vec<SessionPause>,
+    interruption_log: Vec<InterruptionEntry>,
+    note: Option<String>,
+    task_id: Option<i64>,
+    // Both 1–5, given by the user after a focus session ends.
+    focus_rating: Option<i64>,
+    energy_level: Option<i64>,
+    // Local UTC offset when the session was recorded; `None` for sessions from before it was stored.
+    utc_offset_sec: Option<i32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct AnalyticsRange {
     from: Option<i64>,
     to: Option<i64>,
     project_id: Option<i64>,
     tag_id: Option<i64>,
+    profile_id: Option<i64>,
+    task_id: Option<i64>,
+    // Makes `project_id` match its subprojects too, rolling totals up the tree.
+    #[serde(default)]
+    include_subprojects: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -209,19 +632,115 @@ struct AnalyticsSummary {
     total_focus_sec: i64,
     completed_pomodoros: i64,
     streak_days: i64,
+    longest_streak_days: i64,
     interruptions: i64,
     avg_daily_focus_sec: i64,
+    overtime_sec: i64,
+    adjusted_sessions: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct StreakStats {
+    current_days: i64,
+    longest_days: i64,
+    // First and last goal day of the longest streak, as `YYYY-MM-DD`.
+    longest_start: Option<String>,
+    longest_end: Option<String>,
+    // Today never breaks the current streak while its goal is still open.
+    today_goal_met: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum TimeseriesGranularity {
+    #[default]
+    Day,
+    /// ISO weeks, starting on Monday.
+    Week,
+    Month,
+    Year,
+}
+
+impl TimeseriesGranularity {
+    fn bucket_start(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            TimeseriesGranularity::Day => date,
+            TimeseriesGranularity::Week => {
+                date - chrono::Duration::days(i64::from(date.weekday().num_days_from_monday()))
+            }
+            TimeseriesGranularity::Month => date.with_day(1).unwrap_or(date),
+            TimeseriesGranularity::Year => date.with_ordinal(1).unwrap_or(date),
+        }
+    }
+
+    fn next_bucket(self, start: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            TimeseriesGranularity::Day => start.succ_opt(),
+            TimeseriesGranularity::Week => start.checked_add_days(chrono::Days::new(7)),
+            TimeseriesGranularity::Month => start.checked_add_months(Months::new(1)),
+            TimeseriesGranularity::Year => start.checked_add_months(Months::new(12)),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 struct TimeseriesPoint {
+    // First local day of the bucket, as `YYYY-MM-DD`.
     date: String,
     focus_seconds: i64,
     completed_pomodoros: i64,
     interruptions: i64,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum BreakdownDimension {
+    Project,
+    Tag,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct BreakdownRow {
+    // Project or tag id; `None` for the "No project" / "Untagged" bucket.
+    id: Option<i64>,
+    label: String,
+    focus_seconds: i64,
+    completed_pomodoros: i64,
+    interruptions: i64,
+    sessions: i64,
+    // Completed share of the bucket's focus sessions, 0–1.
+    completion_rate: f64,
+    // Percentages of the range total. Sessions with several tags count toward each,
+    // so tag rows can add up to more than 100.
+    focus_percent: f64,
+    pomodoro_percent: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct HeatmapCell {
+    // 0 = Monday … 6 = Sunday, in local time.
+    weekday: u32,
+    hour: u32,
+    focus_seconds: i64,
+    completed_pomodoros: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct TagNamespaceStat {
+    tag_id: i64,
+    name: String,
+    // Focus in sessions carrying this tag or any tag below it. Sessions with tags under
+    // several children count toward each, so rows can add up to more than the namespace.
+    focus_seconds: i64,
+    completed_pomodoros: i64,
+    sessions: i64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 struct Project {
@@ -229,6 +748,9 @@ struct Project {
     name: String,
     color: Option<String>,
     archived: bool,
+    parent_id: Option<i64>,
+    // Names from the root project down to this one, e.g. "Client / Website / Backend".
+    path: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -238,13 +760,19 @@ struct ProjectInput {
     name: String,
     color: Option<String>,
     archived: Option<bool>,
+    // Missing keeps an existing project's parent, `null` moves it to the top level.
+    #[serde(default, deserialize_with = "deserialize_present")]
+    parent_id: Option<Option<i64>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 struct Tag {
     id: i64,
+    // Full path such as "type/bug"; every prefix is a tag of its own.
     name: String,
+    archived: bool,
+    parent_id: Option<i64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -252,6 +780,62 @@ struct Tag {
 struct TagInput {
     id: Option<i64>,
     name: String,
+    archived: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum TaskStatus {
+    #[default]
+    Todo,
+    InProgress,
+    Done,
+}
+
+impl TaskStatus {
+    fn as_db_value(&self) -> &'static str {
+        match self {
+            TaskStatus::Todo => "todo",
+            TaskStatus::InProgress => "in_progress",
+            TaskStatus::Done => "done",
+        }
+    }
+
+    fn from_db_value(value: &str) -> AppResult<Self> {
+        match value {
+            "todo" => Ok(TaskStatus::Todo),
+            "in_progress" => Ok(TaskStatus::InProgress),
+            "done" => Ok(TaskStatus::Done),
+            other => Err(format!("unknown task status: {other}")),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct Task {
+    id: i64,
+    project_id: Option<i64>,
+    title: String,
+    estimate_pomodoros: Option<i64>,
+    status: TaskStatus,
+    position: i64,
+    created_at: i64,
+    completed_at: Option<i64>,
+    // Completed focus sessions recorded against the task.
+    actual_pomodoros: i64,
+    focus_seconds: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskInput {
+    id: Option<i64>,
+    project_id: Option<i64>,
+    title: String,
+    estimate_pomodoros: Option<i64>,
+    #[serde(default)]
+    status: TaskStatus,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -268,6 +852,76 @@ struct ExportRange {
     to: Option<i64>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct DayPlanItem {
+    id: i64,
+    date: String,
+    position: i64,
+    task_id: Option<i64>,
+    project_id: Option<i64>,
+    tag_ids: Vec<i64>,
+    // Task title, or project name for project items.
+    title: Option<String>,
+    planned_pomodoros: i64,
+    completed_pomodoros: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DayPlanItemInput {
+    // Existing items keep the pomodoros already completed against them.
+    id: Option<i64>,
+    task_id: Option<i64>,
+    // Defaults to the task's project.
+    project_id: Option<i64>,
+    tag_ids: Option<Vec<i64>>,
+    planned_pomodoros: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DayPlanReport {
+    date: String,
+    items: Vec<DayPlanItem>,
+    planned_pomodoros: i64,
+    completed_pomodoros: i64,
+    // Every completed focus session that day, planned or not.
+    focus_pomodoros: i64,
+    unplanned_pomodoros: i64,
+    focus_seconds: i64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum TrashKind {
+    Session,
+    Project,
+    Tag,
+}
+
+impl TrashKind {
+    fn table(self) -> &'static str {
+        match self {
+            TrashKind::Session => "sessions",
+            TrashKind::Project => "projects",
+            TrashKind::Tag => "tags",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct TrashItem {
+    kind: TrashKind,
+    id: i64,
+    // Project or tag name; the phase label for sessions.
+    label: String,
+    // Session start, so the UI can tell deleted sessions apart.
+    started_at: Option<i64>,
+    deleted_at: i64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct ResetAllResult {
@@ -280,12 +934,43 @@ struct ResetAllResult {
 struct PhaseCompletedEvent {
     completed_phase: TimerPhase,
     next_phase: TimerPhase,
+    completed_label: String,
+    next_label: String,
+    // The focus session the UI should ask to rate; `None` after breaks.
+    rate_session_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PhaseRecoveredEvent {
+    session: SessionRecord,
+    #[serde(flatten)]
+    phase: PhaseCompletedEvent,
+}
+
+struct ResetSnapshot {
+    path: PathBuf,
+    // Settings as the reset left them; any change since then blocks the undo.
+    settings: serde_json::Value,
 }
 
 struct AppModel {
     conn: Connection,
     settings: AppSettings,
     timer: TimerState,
+    plan: Option<CyclePlan>,
+    profile: Option<TimerProfile>,
+    // Copy of the database taken by the last `reset_all_data`, kept until the app exits.
+    reset_snapshot: Option<ResetSnapshot>,
+    // Phase recovered at startup, held until the UI asks for it with `timer_take_recovered`.
+    recovered: Option<PhaseRecoveredEvent>,
+}
+
+impl AppModel {
+    /// Settings with the active timer profile's durations applied.
+    fn timer_settings(&self) -> AppSettings {
+        profile_settings(&self.settings, self.profile.as_ref())
+    }
 }
 
 struct RemoteServerHandle {
@@ -313,18 +998,120 @@ fn lock_model<'a>(state: &'a State<'_, AppState>) -> AppResult<MutexGuard<'a, Ap
     state.model.lock().map_err(|e| e.to_string())
 }
 
-fn init_database(conn: &Connection) -> AppResult<()> {
-    conn.execute_batch(
-        r#"
-        PRAGMA foreign_keys = ON;
+/// Schema changes in the order they were introduced; `PRAGMA user_version` records how many ran.
+///
+/// Append new steps at the end and never edit a released one.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        description: "base schema",
+        apply: migrate_base_schema,
+    },
+    Migration {
+        description: "tables and columns added before schema versioning",
+        apply: migrate_unversioned_additions,
+    },
+    Migration {
+        description: "session notes full-text index",
+        apply: migrate_session_notes_fts,
+    },
+    Migration {
+        description: "soft delete for sessions, projects and tags",
+        apply: migrate_soft_delete,
+    },
+    Migration {
+        description: "focus quality and energy ratings",
+        apply: migrate_session_ratings,
+    },
+    Migration {
+        description: "per-session UTC offset",
+        apply: migrate_session_utc_offset,
+    },
+    Migration {
+        description: "parents for tags named before nesting",
+        apply: migrate_nested_tag_parents,
+    },
+];
 
-        CREATE TABLE IF NOT EXISTS settings (
-            key TEXT PRIMARY KEY,
-            value TEXT NOT NULL
-        );
+const SCHEMA_VERSION: i64 = MIGRATIONS.len() as i64;
 
-        CREATE TABLE IF NOT EXISTS projects (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
+struct Migration {
+    description: &'static str,
+    apply: fn(&Connection) -> AppResult<()>,
+}
+
+/// Opens the database at `path`, backing it up before any pending migration runs.
+fn open_database(path: &Path) -> AppResult<Connection> {
+    let conn = Connection::open(path).map_err(|e| e.to_string())?;
+    let version = schema_version(&conn)?;
+    if version < SCHEMA_VERSION && has_user_tables(&conn)? {
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "pomodoro.db".to_string());
+        let backup_path = path.with_file_name(format!("{file_name}.v{version}-{}.bak", now_ts()));
+        backup_database(&conn, &backup_path)?;
+    }
+    init_database(&conn)?;
+    Ok(conn)
+}
+
+fn init_database(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch("PRAGMA foreign_keys = ON;")
+        .map_err(|e| e.to_string())?;
+
+    let version = schema_version(conn)?;
+    if version > SCHEMA_VERSION {
+        return Err(format!(
+            "database schema version {version} is newer than this app supports ({SCHEMA_VERSION}); update the app to open it"
+        ));
+    }
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        let target = index as i64 + 1;
+        let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+        (migration.apply)(&tx)
+            .and_then(|_| {
+                tx.pragma_update(None, "user_version", target)
+                    .map_err(|e| e.to_string())
+            })
+            .map_err(|e| format!("migration {target} ({}) failed: {e}", migration.description))?;
+        tx.commit().map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+fn schema_version(conn: &Connection) -> AppResult<i64> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|e| e.to_string())
+}
+
+fn has_user_tables(conn: &Connection) -> AppResult<bool> {
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%')",
+        [],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Writes a consistent copy of the database to `backup_path`.
+fn backup_database(conn: &Connection, backup_path: &Path) -> AppResult<()> {
+    conn.execute("VACUUM INTO ?1", params![backup_path.to_string_lossy()])
+        .map_err(|e| format!("failed to back up database before upgrading: {e}"))?;
+    Ok(())
+}
+
+fn migrate_base_schema(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS projects (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
             name TEXT NOT NULL UNIQUE,
             color TEXT,
             archived INTEGER NOT NULL DEFAULT 0,
@@ -362,11 +1149,307 @@ fn init_database(conn: &Connection) -> AppResult<()> {
         CREATE INDEX IF NOT EXISTS idx_session_tags_tag_id ON session_tags(tag_id);
         "#,
     )
+    .map_err(|e| e.to_string())
+}
+
+/// Unversioned databases may stop at any point of this history, so every step checks first.
+fn migrate_unversioned_additions(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS timer_profiles (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            focus_min INTEGER NOT NULL,
+            short_break_min INTEGER NOT NULL,
+            long_break_min INTEGER NOT NULL,
+            long_break_every INTEGER NOT NULL,
+            created_at INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS tasks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_id INTEGER,
+            title TEXT NOT NULL,
+            estimate_pomodoros INTEGER,
+            status TEXT NOT NULL DEFAULT 'todo',
+            position INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL,
+            completed_at INTEGER,
+            FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE SET NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS day_plan_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            plan_date TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            task_id INTEGER,
+            project_id INTEGER,
+            planned_pomodoros INTEGER NOT NULL,
+            completed_pomodoros INTEGER NOT NULL DEFAULT 0,
+            FOREIGN KEY(task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+            FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE SET NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS day_plan_item_tags (
+            item_id INTEGER NOT NULL,
+            tag_id INTEGER NOT NULL,
+            PRIMARY KEY (item_id, tag_id),
+            FOREIGN KEY(item_id) REFERENCES day_plan_items(id) ON DELETE CASCADE,
+            FOREIGN KEY(tag_id) REFERENCES tags(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS cycle_plans (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            created_at INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS cycle_plan_steps (
+            plan_id INTEGER NOT NULL,
+            position INTEGER NOT NULL,
+            phase TEXT NOT NULL,
+            label TEXT NOT NULL,
+            duration_min INTEGER NOT NULL,
+            counts_as_focus INTEGER NOT NULL,
+            PRIMARY KEY (plan_id, position),
+            FOREIGN KEY(plan_id) REFERENCES cycle_plans(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS session_pauses (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id INTEGER NOT NULL,
+            paused_at INTEGER NOT NULL,
+            resumed_at INTEGER,
+            FOREIGN KEY(session_id) REFERENCES sessions(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS interruption_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id INTEGER NOT NULL,
+            occurred_at INTEGER NOT NULL,
+            kind TEXT,
+            note TEXT,
+            paused INTEGER NOT NULL,
+            FOREIGN KEY(session_id) REFERENCES sessions(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_session_pauses_session_id ON session_pauses(session_id);
+        CREATE INDEX IF NOT EXISTS idx_interruption_log_session_id ON interruption_log(session_id);
+        CREATE INDEX IF NOT EXISTS idx_tasks_project_id ON tasks(project_id, position);
+        CREATE INDEX IF NOT EXISTS idx_day_plan_items_date ON day_plan_items(plan_date, position);
+        "#,
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Databases created before cycle plans only ever recorded built-in focus as focus time.
+    if ensure_column(
+        conn,
+        "sessions",
+        "counts_as_focus",
+        "INTEGER NOT NULL DEFAULT 0",
+    )? {
+        conn.execute(
+            "UPDATE sessions SET counts_as_focus = 1 WHERE phase = 'focus'",
+            [],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    ensure_column(
+        conn,
+        "sessions",
+        "profile_id",
+        "INTEGER REFERENCES timer_profiles(id) ON DELETE SET NULL",
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_sessions_profile_id ON sessions(profile_id)",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+    if ensure_column(
+        conn,
+        "sessions",
+        "planned_duration_sec",
+        "INTEGER NOT NULL DEFAULT 0",
+    )? {
+        conn.execute(
+            "UPDATE sessions SET planned_duration_sec = duration_sec",
+            [],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    ensure_column(
+        conn,
+        "sessions",
+        "overtime_sec",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
+    ensure_column(
+        conn,
+        "sessions",
+        "adjusted_sec",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
+    ensure_column(conn, "sessions", "note", "TEXT")?;
+    ensure_column(conn, "tags", "archived", "INTEGER NOT NULL DEFAULT 0")?;
+    ensure_column(
+        conn,
+        "tags",
+        "parent_id",
+        "INTEGER REFERENCES tags(id) ON DELETE SET NULL",
+    )?;
+    ensure_column(
+        conn,
+        "projects",
+        "parent_id",
+        "INTEGER REFERENCES projects(id) ON DELETE SET NULL",
+    )?;
+    ensure_column(
+        conn,
+        "sessions",
+        "task_id",
+        "INTEGER REFERENCES tasks(id) ON DELETE SET NULL",
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_sessions_task_id ON sessions(task_id)",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+fn migrate_session_notes_fts(conn: &Connection) -> AppResult<()> {
+    let fts_exists: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE name = 'session_notes_fts')",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    // External-content index over `sessions.note`, kept in sync by triggers.
+    conn.execute_batch(
+        r#"
+        CREATE VIRTUAL TABLE IF NOT EXISTS session_notes_fts USING fts5(
+            note,
+            content = 'sessions',
+            content_rowid = 'id',
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
+
+        CREATE TRIGGER IF NOT EXISTS sessions_notes_ai AFTER INSERT ON sessions BEGIN
+            INSERT INTO session_notes_fts (rowid, note) VALUES (new.id, new.note);
+        END;
+        CREATE TRIGGER IF NOT EXISTS sessions_notes_ad AFTER DELETE ON sessions BEGIN
+            INSERT INTO session_notes_fts (session_notes_fts, rowid, note) VALUES ('delete', old.id, old.note);
+        END;
+        CREATE TRIGGER IF NOT EXISTS sessions_notes_au AFTER UPDATE OF note ON sessions BEGIN
+            INSERT INTO session_notes_fts (session_notes_fts, rowid, note) VALUES ('delete', old.id, old.note);
+            INSERT INTO session_notes_fts (rowid, note) VALUES (new.id, new.note);
+        END;
+        "#,
+    )
     .map_err(|e| e.to_string())?;
+    if !fts_exists {
+        conn.execute(
+            "INSERT INTO session_notes_fts (session_notes_fts) VALUES ('rebuild')",
+            [],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+fn migrate_soft_delete(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE sessions ADD COLUMN deleted_at INTEGER;
+        ALTER TABLE projects ADD COLUMN deleted_at INTEGER;
+        ALTER TABLE tags ADD COLUMN deleted_at INTEGER;
+        CREATE INDEX idx_sessions_deleted_at ON sessions(deleted_at);
+        "#,
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn migrate_session_ratings(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE sessions ADD COLUMN focus_rating INTEGER;
+        ALTER TABLE sessions ADD COLUMN energy_level INTEGER;
+        "#,
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn migrate_session_utc_offset(conn: &Connection) -> AppResult<()> {
+    conn.execute("ALTER TABLE sessions ADD COLUMN utc_offset_sec INTEGER", [])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Links tags such as "type/bug" that were created before nesting to their ancestors,
+/// creating missing ones. Names that cannot be nested, e.g. because an ancestor name is
+/// held by a trashed tag, keep no parent until they are renamed.
+fn migrate_nested_tag_parents(conn: &Connection) -> AppResult<()> {
+    let legacy: Vec<(i64, String)> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, name FROM tags
+                 WHERE parent_id IS NULL AND deleted_at IS NULL AND instr(name, '/') > 0",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?;
+        rows.collect::<rusqlite::Result<_>>()
+            .map_err(|e| e.to_string())?
+    };
 
+    for (id, name) in legacy {
+        let Some((parent, _)) = name.rsplit_once('/') else {
+            continue;
+        };
+        if normalize_tag_name(&name).ok().as_deref() != Some(name.as_str()) {
+            continue;
+        }
+        if let Ok(parent_id) = ensure_tag_path(conn, parent) {
+            conn.execute(
+                "UPDATE tags SET parent_id = ?1 WHERE id = ?2",
+                params![parent_id, id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
     Ok(())
 }
 
+/// Adds `column` to `table` when an older database lacks it. Returns `true` if it was added.
+fn ensure_column(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> AppResult<bool> {
+    let exists: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+            params![table, column],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if exists > 0 {
+        return Ok(false);
+    }
+
+    conn.execute(
+        &format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"),
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
 fn save_json_setting<T: Serialize>(conn: &Connection, key: &str, value: &T) -> AppResult<()> {
     let json = serde_json::to_string(value).map_err(|e| e.to_string())?;
     conn.execute(
@@ -410,6 +1493,54 @@ fn normalize_settings(mut settings: AppSettings) -> AppSettings {
         _ => "light".to_string(),
     };
     settings.remote_control_port = settings.remote_control_port.clamp(1024, 65535);
+    settings.auto_start_delay_sec = settings.auto_start_delay_sec.clamp(0, 300);
+    settings.auto_stop_after_cycles = settings.auto_stop_after_cycles.clamp(0, 24);
+    settings.trash_retention_days = settings.trash_retention_days.clamp(0, 365);
+    let day_start = parse_day_start(&settings.day_starts_at).unwrap_or(0);
+    settings.day_starts_at = format!("{:02}:{:02}", day_start / 60, day_start % 60);
+    settings.daily_goal_pomodoros = settings.daily_goal_pomodoros.clamp(0, 24);
+    settings.rest_weekdays.retain(|weekday| *weekday < 7);
+    settings.rest_weekdays.sort_unstable();
+    settings.rest_weekdays.dedup();
+    settings.vacation_dates = settings
+        .vacation_dates
+        .iter()
+        .filter_map(|date| NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok())
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .collect();
+    settings.vacation_dates.sort();
+    settings.vacation_dates.dedup();
+    settings
+}
+
+/// Minutes after midnight for an "HH:MM" day start.
+fn parse_day_start(value: &str) -> Option<i64> {
+    let (hours, minutes) = value.trim().split_once(':')?;
+    let hours: i64 = hours.parse().ok()?;
+    let minutes: i64 = minutes.parse().ok()?;
+    ((0..24).contains(&hours) && (0..60).contains(&minutes)).then_some(hours * 60 + minutes)
+}
+
+fn normalize_profile(mut profile: TimerProfileInput) -> AppResult<TimerProfileInput> {
+    profile.name = profile.name.trim().to_string();
+    if profile.name.is_empty() {
+        return Err("timer profile name cannot be empty".to_string());
+    }
+    profile.focus_min = profile.focus_min.clamp(1, 180);
+    profile.short_break_min = profile.short_break_min.clamp(1, 60);
+    profile.long_break_min = profile.long_break_min.clamp(1, 90);
+    profile.long_break_every = profile.long_break_every.clamp(2, 10);
+    Ok(profile)
+}
+
+fn profile_settings(settings: &AppSettings, profile: Option<&TimerProfile>) -> AppSettings {
+    let mut settings = settings.clone();
+    if let Some(profile) = profile {
+        settings.focus_min = profile.focus_min;
+        settings.short_break_min = profile.short_break_min;
+        settings.long_break_min = profile.long_break_min;
+        settings.long_break_every = profile.long_break_every;
+    }
     settings
 }
 
@@ -436,9 +1567,53 @@ fn load_or_create_settings(conn: &Connection) -> AppResult<AppSettings> {
     Ok(settings)
 }
 
-fn normalize_timer_state(mut timer: TimerState, settings: &AppSettings) -> TimerState {
-    timer.phase_total_seconds = settings.duration_for_phase_seconds(&timer.phase);
-    if timer.remaining_seconds <= 0 || timer.remaining_seconds > timer.phase_total_seconds {
+/// Forgets everything recorded for the current phase so it starts over from scratch.
+fn discard_phase_progress(timer: &mut TimerState) {
+    timer.started_at = None;
+    timer.remaining_seconds = 0;
+    timer.adjusted_seconds = 0;
+    timer.interruptions = 0;
+    timer.in_overtime = false;
+    timer.overtime_seconds = 0;
+    timer.pauses.clear();
+    timer.interruption_log.clear();
+    timer.session_note = None;
+}
+
+fn normalize_timer_state(
+    mut timer: TimerState,
+    settings: &AppSettings,
+    plan: Option<&CyclePlan>,
+) -> TimerState {
+    let selected_plan = plan
+        .filter(|plan| Some(plan.id) == timer.plan_id)
+        .filter(|plan| !plan.steps.is_empty());
+    if let Some(plan) = selected_plan {
+        let step_count = plan.steps.len() as i64;
+        if !(0..step_count).contains(&timer.plan_step) {
+            // The selected plan was shortened; wrap around to a step that still exists.
+            timer.plan_step = timer.plan_step.rem_euclid(step_count);
+            discard_phase_progress(&mut timer);
+        }
+    } else if timer.plan_id.is_some() {
+        // The selected plan was deleted; fall back to the built-in cycle.
+        timer.plan_id = None;
+        timer.plan_step = 0;
+        timer.phase = TimerPhase::Focus;
+        discard_phase_progress(&mut timer);
+    }
+    if timer.plan_id.is_none() && matches!(timer.phase, TimerPhase::Custom(_)) {
+        timer.phase = TimerPhase::Focus;
+        discard_phase_progress(&mut timer);
+    }
+
+    let step = current_step(&timer, settings, plan);
+    apply_step(&mut timer, &step);
+    // A running timer whose target has passed is recovered by the worker, not reset here.
+    let awaiting_recovery = timer.is_running && timer.target_ends_at.is_some();
+    if (timer.remaining_seconds <= 0 && !timer.in_overtime && !awaiting_recovery)
+        || timer.remaining_seconds > timer.phase_total_seconds
+    {
         timer.remaining_seconds = timer.phase_total_seconds;
         timer.is_running = false;
         timer.target_ends_at = None;
@@ -450,12 +1625,50 @@ fn normalize_timer_state(mut timer: TimerState, settings: &AppSettings) -> Timer
     timer
 }
 
-fn load_or_create_timer(conn: &Connection, settings: &AppSettings) -> AppResult<TimerState> {
-    let timer = load_json_setting::<TimerState>(conn, TIMER_STATE_KEY)?
+fn load_or_create_timer(
+    conn: &Connection,
+    settings: &AppSettings,
+) -> AppResult<(TimerState, Option<CyclePlan>, Option<TimerProfile>)> {
+    let mut timer = load_json_setting::<TimerState>(conn, TIMER_STATE_KEY)?
         .unwrap_or_else(|| TimerState::default_with_settings(settings));
-    let timer = normalize_timer_state(timer, settings);
+    let plan = match timer.plan_id {
+        Some(plan_id) => fetch_cycle_plan(conn, plan_id)?,
+        None => None,
+    };
+    let profile = match timer.profile_id {
+        Some(profile_id) => fetch_timer_profile(conn, profile_id)?,
+        None => None,
+    };
+    if profile.is_none() {
+        timer.profile_id = None;
+    }
+    let timer = normalize_timer_state(
+        timer,
+        &profile_settings(settings, profile.as_ref()),
+        plan.as_ref(),
+    );
     save_json_setting(conn, TIMER_STATE_KEY, &timer)?;
-    Ok(timer)
+    Ok((timer, plan, profile))
+}
+
+/// Resolves the step the timer is currently in: a cycle plan step, or a built-in phase.
+fn current_step(
+    timer: &TimerState,
+    settings: &AppSettings,
+    plan: Option<&CyclePlan>,
+) -> CyclePlanStep {
+    plan.filter(|plan| Some(plan.id) == timer.plan_id)
+        .and_then(|plan| plan.steps.get(timer.plan_step as usize))
+        .cloned()
+        .unwrap_or_else(|| CyclePlanStep::builtin(timer.phase.clone(), settings))
+}
+
+/// Takes over the step's phase and duration, keeping any adjustment made to the current phase.
+fn apply_step(timer: &mut TimerState, step: &CyclePlanStep) {
+    timer.phase = step.phase.clone();
+    timer.phase_label = step.label.clone();
+    timer.counts_as_focus = step.counts_as_focus;
+    timer.phase_total_seconds = step.duration_min * 60 + timer.adjusted_seconds;
 }
 
 fn save_timer_state(conn: &Connection, timer: &TimerState) -> AppResult<()> {
@@ -465,9 +1678,152 @@ fn save_timer_state(conn: &Connection, timer: &TimerState) -> AppResult<()> {
 fn refresh_remaining(timer: &mut TimerState) {
     if timer.is_running {
         if let Some(target_ends_at) = timer.target_ends_at {
-            timer.remaining_seconds = (target_ends_at - now_ts()).max(0);
+            let now = now_ts();
+            timer.remaining_seconds = (target_ends_at - now).max(0);
+            if timer.in_overtime {
+                timer.overtime_seconds = (now - target_ends_at).max(0);
+            }
+        }
+    }
+}
+
+/// Puts the timer into the running state for the rest of the current phase.
+fn begin_running(timer: &mut TimerState) {
+    if timer.remaining_seconds <= 0 && !timer.in_overtime {
+        timer.remaining_seconds = timer.phase_total_seconds;
+    }
+    if timer.started_at.is_none() {
+        timer.started_at = Some(now_ts());
+    }
+    if let Some(pause) = timer.pauses.last_mut() {
+        if pause.resumed_at.is_none() {
+            pause.resumed_at = Some(now_ts());
+        }
+    }
+    timer.is_running = true;
+    // In overtime the target lies in the past, so overtime keeps counting from where it paused.
+    timer.target_ends_at = Some(now_ts() + timer.remaining_seconds - timer.overtime_seconds);
+    timer.auto_start_at = None;
+}
+
+fn normalize_interruption_reason(reason: InterruptionReason) -> InterruptionReason {
+    let note = reason
+        .note
+        .map(|note| note.trim().chars().take(280).collect::<String>())
+        .filter(|note| !note.is_empty());
+    InterruptionReason {
+        kind: reason.kind,
+        note,
+    }
+}
+
+const MAX_SESSION_NOTE_CHARS: usize = 4000;
+
+fn normalize_session_note(note: Option<String>) -> Option<String> {
+    note.map(|note| {
+        note.trim()
+            .chars()
+            .take(MAX_SESSION_NOTE_CHARS)
+            .collect::<String>()
+    })
+    .filter(|note| !note.is_empty())
+}
+
+/// Stops a running timer, counting the pause as an interruption during focus.
+fn pause_timer(timer: &mut TimerState, reason: Option<InterruptionReason>) {
+    if timer.is_running {
+        if timer.counts_as_focus {
+            timer.interruptions += 1;
+            let reason = normalize_interruption_reason(reason.unwrap_or_default());
+            timer.interruption_log.push(InterruptionEntry {
+                occurred_at: now_ts(),
+                kind: reason.kind,
+                note: reason.note,
+                paused: true,
+            });
         }
+        timer.pauses.push(SessionPause {
+            paused_at: now_ts(),
+            resumed_at: None,
+        });
+    }
+    timer.is_running = false;
+    timer.target_ends_at = None;
+    timer.auto_start_at = None;
+}
+
+/// Decides whether the phase the timer just advanced into starts by itself.
+fn schedule_auto_start(timer: &mut TimerState, settings: &AppSettings, finished_focus: bool) {
+    if finished_focus {
+        timer.auto_run_cycles += 1;
+    }
+
+    let enabled = if timer.counts_as_focus {
+        settings.auto_start_focus
+    } else {
+        settings.auto_start_breaks
+    };
+    let limit_reached = timer.counts_as_focus
+        && settings.auto_stop_after_cycles > 0
+        && timer.auto_run_cycles >= settings.auto_stop_after_cycles;
+
+    if !enabled || limit_reached {
+        timer.auto_run_cycles = 0;
+        return;
+    }
+
+    if settings.auto_start_delay_sec > 0 {
+        timer.auto_start_at = Some(now_ts() + settings.auto_start_delay_sec);
+    } else {
+        begin_running(timer);
+    }
+}
+
+const MAX_ADJUST_SECONDS: i64 = 60 * 60;
+const MAX_PHASE_SECONDS: i64 = 4 * 60 * 60;
+
+/// Shifts the current phase by `delta_seconds`, keeping at least one second and at most
+/// four hours in the phase. Returns the delta that was actually applied.
+fn adjust_timer(timer: &mut TimerState, delta_seconds: i64) -> AppResult<i64> {
+    if timer.in_overtime {
+        return Err("the phase is already in overtime; end it instead".to_string());
+    }
+    if delta_seconds.abs() > MAX_ADJUST_SECONDS {
+        return Err(format!(
+            "adjustments are limited to {} minutes at a time",
+            MAX_ADJUST_SECONDS / 60
+        ));
+    }
+
+    refresh_remaining(timer);
+    let applied = delta_seconds
+        .max(1 - timer.remaining_seconds)
+        .min(MAX_PHASE_SECONDS - timer.phase_total_seconds);
+
+    timer.remaining_seconds += applied;
+    timer.phase_total_seconds += applied;
+    timer.adjusted_seconds += applied;
+    if let Some(target_ends_at) = timer.target_ends_at {
+        timer.target_ends_at = Some(target_ends_at + applied);
     }
+
+    Ok(applied)
+}
+
+/// Puts the current phase back to its untouched state without recording anything.
+fn reset_phase(timer: &mut TimerState) {
+    timer.phase_total_seconds -= timer.adjusted_seconds;
+    timer.adjusted_seconds = 0;
+    timer.remaining_seconds = timer.phase_total_seconds;
+    timer.is_running = false;
+    timer.started_at = None;
+    timer.target_ends_at = None;
+    timer.interruptions = 0;
+    timer.auto_start_at = None;
+    timer.in_overtime = false;
+    timer.overtime_seconds = 0;
+    timer.pauses.clear();
+    timer.interruption_log.clear();
 }
 
 fn format_seconds(seconds: i64) -> String {
@@ -483,11 +1839,12 @@ fn update_tray_title(app: &AppHandle, timer: &TimerState) {
         } else {
             "Paused"
         };
-        let title = format!(
-            "{} {} {status}",
-            timer.phase,
+        let clock = if timer.in_overtime {
+            format!("+{}", format_seconds(timer.overtime_seconds))
+        } else {
             format_seconds(timer.remaining_seconds)
-        );
+        };
+        let title = format!("{} {clock} {status}", timer.phase_label);
         let _ = tray.set_title(Some(&title));
     }
 }
@@ -503,8 +1860,13 @@ fn record_session(
     completed: bool,
     ended_at: i64,
 ) -> AppResult<SessionRecord> {
+    let overtime = if timer.in_overtime {
+        timer.overtime_seconds.max(0)
+    } else {
+        0
+    };
     let elapsed = if completed {
-        timer.phase_total_seconds
+        timer.phase_total_seconds + overtime
     } else {
         (timer.phase_total_seconds - timer.remaining_seconds).clamp(0, timer.phase_total_seconds)
     };
@@ -513,14 +1875,16 @@ fn record_session(
         .started_at
         .unwrap_or_else(|| ended_at - elapsed.max(1));
 
-    let project_id = match timer.phase {
-        TimerPhase::Focus => timer.current_project_id,
-        _ => None,
+    let (project_id, task_id) = if timer.counts_as_focus {
+        (timer.current_project_id, timer.current_task_id)
+    } else {
+        (None, None)
     };
 
+    let utc_offset_sec = local_utc_offset(ended_at);
     conn.execute(
-        "INSERT INTO sessions (started_at, ended_at, phase, duration_sec, completed, interruptions, project_id)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        "INSERT INTO sessions (started_at, ended_at, phase, duration_sec, completed, interruptions, project_id, counts_as_focus, profile_id, planned_duration_sec, overtime_sec, adjusted_sec, note, task_id, utc_offset_sec)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
         params![
             started_at,
             ended_at,
@@ -529,6 +1893,14 @@ fn record_session(
             completed as i64,
             timer.interruptions,
             project_id,
+            timer.counts_as_focus as i64,
+            timer.profile_id,
+            timer.phase_total_seconds,
+            overtime,
+            timer.adjusted_seconds,
+            timer.session_note,
+            task_id,
+            utc_offset_sec,
         ],
     )
     .map_err(|e| e.to_string())?;
@@ -536,15 +1908,37 @@ fn record_session(
     let id = conn.last_insert_rowid();
     let mut tag_ids = Vec::new();
 
-    if timer.phase == TimerPhase::Focus {
-        for tag_id in &timer.current_tag_ids {
-            conn.execute(
-                "INSERT OR IGNORE INTO session_tags (session_id, tag_id) VALUES (?1, ?2)",
-                params![id, tag_id],
-            )
-            .map_err(|e| e.to_string())?;
-            tag_ids.push(*tag_id);
-        }
+    for pause in &timer.pauses {
+        conn.execute(
+            "INSERT INTO session_pauses (session_id, paused_at, resumed_at) VALUES (?1, ?2, ?3)",
+            params![id, pause.paused_at, pause.resumed_at],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    for entry in &timer.interruption_log {
+        conn.execute(
+            "INSERT INTO interruption_log (session_id, occurred_at, kind, note, paused) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                id,
+                entry.occurred_at,
+                entry.kind.as_ref().map(InterruptionKind::as_db_value),
+                entry.note,
+                entry.paused as i64,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    if timer.counts_as_focus {
+        for tag_id in &timer.current_tag_ids {
+            conn.execute(
+                "INSERT OR IGNORE INTO session_tags (session_id, tag_id) VALUES (?1, ?2)",
+                params![id, tag_id],
+            )
+            .map_err(|e| e.to_string())?;
+            tag_ids.push(*tag_id);
+        }
     }
 
     Ok(SessionRecord {
@@ -557,29 +1951,75 @@ fn record_session(
         interruptions: timer.interruptions,
         project_id,
         tag_ids,
+        counts_as_focus: timer.counts_as_focus,
+        profile_id: timer.profile_id,
+        planned_duration_sec: timer.phase_total_seconds,
+        overtime_sec: overtime,
+        adjusted_sec: timer.adjusted_seconds,
+        pauses: timer.pauses.clone(),
+        interruption_log: timer.interruption_log.clone(),
+        note: timer.session_note.clone(),
+        task_id,
+        focus_rating: None,
+        energy_level: None,
+        utc_offset_sec: Some(utc_offset_sec),
     })
 }
 
-fn advance_timer(timer: &mut TimerState, settings: &AppSettings) {
-    let next_phase = match timer.phase {
-        TimerPhase::Focus => {
-            timer.cycle_index += 1;
-            if timer.cycle_index % settings.long_break_every == 0 {
-                TimerPhase::LongBreak
-            } else {
-                TimerPhase::ShortBreak
-            }
+fn advance_timer(timer: &mut TimerState, settings: &AppSettings, plan: Option<&CyclePlan>) {
+    if timer.counts_as_focus {
+        timer.cycle_index += 1;
+    }
+
+    let next_step = match plan.filter(|plan| Some(plan.id) == timer.plan_id) {
+        Some(plan) if !plan.steps.is_empty() => {
+            timer.plan_step = (timer.plan_step + 1) % plan.steps.len() as i64;
+            plan.steps[timer.plan_step as usize].clone()
+        }
+        _ => {
+            let next_phase = match timer.phase {
+                TimerPhase::Focus => {
+                    if timer.cycle_index % settings.long_break_every == 0 {
+                        TimerPhase::LongBreak
+                    } else {
+                        TimerPhase::ShortBreak
+                    }
+                }
+                _ => TimerPhase::Focus,
+            };
+            CyclePlanStep::builtin(next_phase, settings)
         }
-        TimerPhase::ShortBreak | TimerPhase::LongBreak => TimerPhase::Focus,
     };
 
-    timer.phase = next_phase;
-    timer.phase_total_seconds = settings.duration_for_phase_seconds(&timer.phase);
+    timer.adjusted_seconds = 0;
+    apply_step(timer, &next_step);
     timer.remaining_seconds = timer.phase_total_seconds;
     timer.is_running = false;
     timer.started_at = None;
     timer.target_ends_at = None;
     timer.interruptions = 0;
+    timer.auto_start_at = None;
+    timer.in_overtime = false;
+    timer.overtime_seconds = 0;
+    timer.pauses.clear();
+    timer.interruption_log.clear();
+    timer.session_note = None;
+}
+
+/// Re-applies the current step's duration when the phase has not been started yet.
+fn sync_idle_timer(model: &mut AppModel) {
+    if model.timer.started_at.is_some() {
+        return;
+    }
+    let settings = model.timer_settings();
+    let step = current_step(&model.timer, &settings, model.plan.as_ref());
+    apply_step(&mut model.timer, &step);
+    model.timer.remaining_seconds = model.timer.phase_total_seconds;
+}
+
+/// The session to ask the user to rate once its phase ends: focus sessions that logged time.
+fn rating_prompt_for(session: &SessionRecord) -> Option<i64> {
+    (session.counts_as_focus && session.duration_sec > 0).then_some(session.id)
 }
 
 fn complete_and_advance(
@@ -588,20 +2028,44 @@ fn complete_and_advance(
     completed: bool,
 ) -> AppResult<(SessionRecord, PhaseCompletedEvent, TimerState)> {
     let finished_phase = model.timer.phase.clone();
-    let session = record_session(&model.conn, &model.timer, completed, now_ts())?;
+    let finished_label = model.timer.phase_label.clone();
+    let finished_focus = model.timer.counts_as_focus;
+    let now = now_ts();
+    let session = record_session(&model.conn, &model.timer, completed, now)?;
+    let day_start_min = model.settings.day_start_minutes();
+    if finished_focus && completed {
+        advance_day_plan(
+            &model.conn,
+            &mut model.timer,
+            session.ended_at,
+            now,
+            day_start_min,
+        )?;
+    }
 
-    advance_timer(&mut model.timer, &model.settings);
+    let settings = model.timer_settings();
+    advance_timer(&mut model.timer, &settings, model.plan.as_ref());
+    if model.timer.counts_as_focus {
+        select_day_plan_item(&model.conn, &mut model.timer, now, day_start_min)?;
+    }
+    // Only phases that ran to zero roll on by themselves; skipping leaves the timer idle.
+    if completed {
+        schedule_auto_start(&mut model.timer, &settings, finished_focus);
+    }
     save_timer_state(&model.conn, &model.timer)?;
 
     let event = PhaseCompletedEvent {
         completed_phase: finished_phase,
         next_phase: model.timer.phase.clone(),
+        completed_label: finished_label,
+        next_label: model.timer.phase_label.clone(),
+        rate_session_id: rating_prompt_for(&session),
     };
 
     if model.settings.notifications_enabled {
         let body = format!(
             "{} complete. Next: {}",
-            event.completed_phase, event.next_phase
+            event.completed_label, event.next_label
         );
         let _ = app
             .notification()
@@ -614,17 +2078,107 @@ fn complete_and_advance(
     Ok((session, event, model.timer.clone()))
 }
 
+/// Records a running phase whose end passed while the app was closed or the machine slept.
+///
+/// The session ends at `target_ends_at` (plus any overtime counted before the gap) and the
+/// timer advances to the next phase without auto-starting it.
+fn recover_expired_phase(model: &mut AppModel, now: i64) -> AppResult<Option<PhaseRecoveredEvent>> {
+    let Some(target_ends_at) = model.timer.target_ends_at else {
+        return Ok(None);
+    };
+    if !model.timer.is_running || target_ends_at > now {
+        return Ok(None);
+    }
+
+    model.timer.remaining_seconds = 0;
+    // Overtime only covers what was counted before the gap, never the time spent asleep.
+    let overtime = if model.timer.in_overtime {
+        model
+            .timer
+            .overtime_seconds
+            .clamp(0, (now - target_ends_at).max(0))
+    } else {
+        0
+    };
+    model.timer.overtime_seconds = overtime;
+
+    let finished_phase = model.timer.phase.clone();
+    let finished_label = model.timer.phase_label.clone();
+    let session = record_session(&model.conn, &model.timer, true, target_ends_at + overtime)?;
+    let day_start_min = model.settings.day_start_minutes();
+    if model.timer.counts_as_focus {
+        advance_day_plan(
+            &model.conn,
+            &mut model.timer,
+            session.ended_at,
+            now,
+            day_start_min,
+        )?;
+    }
+
+    let settings = model.timer_settings();
+    advance_timer(&mut model.timer, &settings, model.plan.as_ref());
+    if model.timer.counts_as_focus {
+        select_day_plan_item(&model.conn, &mut model.timer, now, day_start_min)?;
+    }
+    save_timer_state(&model.conn, &model.timer)?;
+
+    Ok(Some(PhaseRecoveredEvent {
+        phase: PhaseCompletedEvent {
+            completed_phase: finished_phase,
+            next_phase: model.timer.phase.clone(),
+            completed_label: finished_label,
+            next_label: model.timer.phase_label.clone(),
+            rate_session_id: rating_prompt_for(&session),
+        },
+        session,
+    }))
+}
+
+fn notify_phase_recovered(app: &AppHandle, model: &AppModel, event: &PhaseRecoveredEvent) {
+    if model.settings.notifications_enabled {
+        let body = format!(
+            "{} finished while the app was away and has been recorded. Next: {}",
+            event.phase.completed_label, event.phase.next_label
+        );
+        let _ = app
+            .notification()
+            .builder()
+            .title("Pomodoro update")
+            .body(&body)
+            .show();
+    }
+}
+
+fn notify_overtime_started(app: &AppHandle, model: &AppModel) {
+    if model.settings.notifications_enabled {
+        let body = format!(
+            "{} time is up. Overtime is counting until you end it.",
+            model.timer.phase_label
+        );
+        let _ = app
+            .notification()
+            .builder()
+            .title("Pomodoro update")
+            .body(&body)
+            .show();
+    }
+}
+
 fn setup_tray(app: &AppHandle) -> AppResult<()> {
     let toggle = MenuItem::with_id(app, "toggle", "Start / Pause", true, None::<&str>)
         .map_err(|e| e.to_string())?;
     let skip = MenuItem::with_id(app, "skip", "Skip phase", true, None::<&str>)
         .map_err(|e| e.to_string())?;
+    let reset = MenuItem::with_id(app, "reset", "Reset phase", true, None::<&str>)
+        .map_err(|e| e.to_string())?;
     let open = MenuItem::with_id(app, "open", "Open dashboard", true, None::<&str>)
         .map_err(|e| e.to_string())?;
     let quit =
         MenuItem::with_id(app, "quit", "Quit", true, None::<&str>).map_err(|e| e.to_string())?;
 
-    let menu = Menu::with_items(app, &[&toggle, &skip, &open, &quit]).map_err(|e| e.to_string())?;
+    let menu = Menu::with_items(app, &[&toggle, &skip, &reset, &open, &quit])
+        .map_err(|e| e.to_string())?;
 
     TrayIconBuilder::with_id(TRAY_ID)
         .menu(&menu)
@@ -637,6 +2191,10 @@ fn setup_tray(app: &AppHandle) -> AppResult<()> {
             "skip" => {
                 let _ = tray_skip_timer(app);
             }
+            "reset" => {
+                let state = app.state::<AppState>();
+                let _ = timer_reset_phase_inner(app, state.inner());
+            }
             "open" => {
                 if let Some(window) = app.get_webview_window("main") {
                     let _ = window.show();
@@ -661,20 +2219,14 @@ fn tray_toggle_timer(app: &AppHandle) -> AppResult<()> {
         refresh_remaining(&mut model.timer);
 
         if model.timer.is_running {
-            model.timer.is_running = false;
-            model.timer.target_ends_at = None;
-            if model.timer.phase == TimerPhase::Focus {
-                model.timer.interruptions += 1;
-            }
+            pause_timer(&mut model.timer, None);
+        } else if model.timer.auto_start_at.is_some() {
+            // Toggling during the auto-start grace period cancels it.
+            model.timer.auto_start_at = None;
+            model.timer.auto_run_cycles = 0;
         } else {
-            if model.timer.remaining_seconds <= 0 {
-                model.timer.remaining_seconds = model.timer.phase_total_seconds;
-            }
-            if model.timer.started_at.is_none() {
-                model.timer.started_at = Some(now_ts());
-            }
-            model.timer.is_running = true;
-            model.timer.target_ends_at = Some(now_ts() + model.timer.remaining_seconds);
+            model.timer.auto_run_cycles = 0;
+            begin_running(&mut model.timer);
         }
 
         save_timer_state(&model.conn, &model.timer)?;
@@ -690,7 +2242,9 @@ fn tray_skip_timer(app: &AppHandle) -> AppResult<()> {
         let state = app.state::<AppState>();
         let mut model = state.model.lock().map_err(|e| e.to_string())?;
         refresh_remaining(&mut model.timer);
-        complete_and_advance(app, &mut model, false)?
+        // Ending overtime is finishing the phase, not abandoning it.
+        let completed = model.timer.in_overtime;
+        complete_and_advance(app, &mut model, completed)?
     };
 
     let _ = app.emit("session://completed", &session);
@@ -699,6 +2253,35 @@ fn tray_skip_timer(app: &AppHandle) -> AppResult<()> {
     Ok(())
 }
 
+/// Applies the project, tags, note and task chosen for the current phase.
+fn apply_timer_context(model: &mut AppModel, payload: StartTimerRequest) -> AppResult<()> {
+    let planned_context = (model.timer.current_project_id, model.timer.current_task_id);
+    if let Some(task_id) = payload.task_id {
+        if let Some(task_id) = task_id {
+            let task = fetch_task(&model.conn, task_id)?
+                .ok_or_else(|| format!("task {task_id} not found"))?;
+            if payload.project_id.is_none() && task.project_id.is_some() {
+                model.timer.current_project_id = task.project_id;
+            }
+        }
+        model.timer.current_task_id = task_id;
+    }
+    if let Some(project_id) = payload.project_id {
+        model.timer.current_project_id = project_id;
+    }
+    if let Some(tag_ids) = payload.tag_ids {
+        model.timer.current_tag_ids = tag_ids;
+    }
+    if let Some(note) = payload.note {
+        model.timer.session_note = normalize_session_note(note);
+    }
+    // Picking another project or task by hand takes the phase off the day plan.
+    if (model.timer.current_project_id, model.timer.current_task_id) != planned_context {
+        model.timer.day_plan_item_id = None;
+    }
+    Ok(())
+}
+
 fn timer_start_inner(
     app: &AppHandle,
     state: &AppState,
@@ -708,24 +2291,18 @@ fn timer_start_inner(
         let mut model = state.model.lock().map_err(|e| e.to_string())?;
         refresh_remaining(&mut model.timer);
 
-        if let Some(payload) = payload {
-            if let Some(project_id) = payload.project_id {
-                model.timer.current_project_id = project_id;
-            }
-            if let Some(tag_ids) = payload.tag_ids {
-                model.timer.current_tag_ids = tag_ids;
-            }
-        }
-
-        if model.timer.remaining_seconds <= 0 {
-            model.timer.remaining_seconds = model.timer.phase_total_seconds;
+        // A fresh focus phase works on today's next planned item unless the request says otherwise.
+        if model.timer.started_at.is_none() && model.timer.counts_as_focus {
+            let model = &mut *model;
+            let day_start_min = model.settings.day_start_minutes();
+            select_day_plan_item(&model.conn, &mut model.timer, now_ts(), day_start_min)?;
         }
-        if model.timer.started_at.is_none() {
-            model.timer.started_at = Some(now_ts());
+        if let Some(payload) = payload {
+            apply_timer_context(&mut model, payload)?;
         }
 
-        model.timer.is_running = true;
-        model.timer.target_ends_at = Some(now_ts() + model.timer.remaining_seconds);
+        model.timer.auto_run_cycles = 0;
+        begin_running(&mut model.timer);
 
         save_timer_state(&model.conn, &model.timer)?;
         model.timer.clone()
@@ -735,15 +2312,40 @@ fn timer_start_inner(
     Ok(timer)
 }
 
-fn timer_pause_inner(app: &AppHandle, state: &AppState) -> AppResult<TimerState> {
+fn timer_pause_inner(
+    app: &AppHandle,
+    state: &AppState,
+    reason: Option<InterruptionReason>,
+) -> AppResult<TimerState> {
     let timer = {
         let mut model = state.model.lock().map_err(|e| e.to_string())?;
         refresh_remaining(&mut model.timer);
-        if model.timer.phase == TimerPhase::Focus && model.timer.is_running {
-            model.timer.interruptions += 1;
+        pause_timer(&mut model.timer, reason);
+        save_timer_state(&model.conn, &model.timer)?;
+        model.timer.clone()
+    };
+
+    emit_timer_state(app, &timer);
+    Ok(timer)
+}
+
+fn timer_note_distraction_inner(
+    app: &AppHandle,
+    state: &AppState,
+    reason: InterruptionReason,
+) -> AppResult<TimerState> {
+    let timer = {
+        let mut model = state.model.lock().map_err(|e| e.to_string())?;
+        if !model.timer.counts_as_focus || model.timer.started_at.is_none() {
+            return Err("distractions can only be noted during a focus phase".to_string());
         }
-        model.timer.is_running = false;
-        model.timer.target_ends_at = None;
+        let reason = normalize_interruption_reason(reason);
+        model.timer.interruption_log.push(InterruptionEntry {
+            occurred_at: now_ts(),
+            kind: reason.kind,
+            note: reason.note,
+            paused: false,
+        });
         save_timer_state(&model.conn, &model.timer)?;
         model.timer.clone()
     };
@@ -760,21 +2362,10 @@ fn timer_resume_inner(
     let timer = {
         let mut model = state.model.lock().map_err(|e| e.to_string())?;
         if let Some(payload) = payload {
-            if let Some(project_id) = payload.project_id {
-                model.timer.current_project_id = project_id;
-            }
-            if let Some(tag_ids) = payload.tag_ids {
-                model.timer.current_tag_ids = tag_ids;
-            }
-        }
-        if model.timer.remaining_seconds <= 0 {
-            model.timer.remaining_seconds = model.timer.phase_total_seconds;
-        }
-        if model.timer.started_at.is_none() {
-            model.timer.started_at = Some(now_ts());
+            apply_timer_context(&mut model, payload)?;
         }
-        model.timer.is_running = true;
-        model.timer.target_ends_at = Some(now_ts() + model.timer.remaining_seconds);
+        model.timer.auto_run_cycles = 0;
+        begin_running(&mut model.timer);
         save_timer_state(&model.conn, &model.timer)?;
         model.timer.clone()
     };
@@ -787,7 +2378,8 @@ fn timer_skip_inner(app: &AppHandle, state: &AppState) -> AppResult<TimerState>
     let (session, phase_event, timer) = {
         let mut model = state.model.lock().map_err(|e| e.to_string())?;
         refresh_remaining(&mut model.timer);
-        complete_and_advance(app, &mut model, false)?
+        let completed = model.timer.in_overtime;
+        complete_and_advance(app, &mut model, completed)?
     };
 
     let _ = app.emit("session://completed", &session);
@@ -796,6 +2388,34 @@ fn timer_skip_inner(app: &AppHandle, state: &AppState) -> AppResult<TimerState>
     Ok(timer)
 }
 
+fn timer_reset_phase_inner(app: &AppHandle, state: &AppState) -> AppResult<TimerState> {
+    let timer = {
+        let mut model = state.model.lock().map_err(|e| e.to_string())?;
+        reset_phase(&mut model.timer);
+        save_timer_state(&model.conn, &model.timer)?;
+        model.timer.clone()
+    };
+
+    emit_timer_state(app, &timer);
+    Ok(timer)
+}
+
+fn timer_adjust_inner(
+    app: &AppHandle,
+    state: &AppState,
+    delta_seconds: i64,
+) -> AppResult<TimerState> {
+    let timer = {
+        let mut model = state.model.lock().map_err(|e| e.to_string())?;
+        adjust_timer(&mut model.timer, delta_seconds)?;
+        save_timer_state(&model.conn, &model.timer)?;
+        model.timer.clone()
+    };
+
+    emit_timer_state(app, &timer);
+    Ok(timer)
+}
+
 fn timer_get_state_inner(state: &AppState) -> AppResult<TimerState> {
     let mut model = state.model.lock().map_err(|e| e.to_string())?;
     refresh_remaining(&mut model.timer);
@@ -941,6 +2561,9 @@ fn remote_html() -> String {
           <div class="btns">
             <button class="primary" id="toggle">Start / Pause</button>
             <button class="danger" id="skip">Skip Phase</button>
+            <button id="minus5">&minus;5 min</button>
+            <button id="plus5">+5 min</button>
+            <button id="reset">Reset Phase</button>
           </div>
           <div class="sp"></div>
           <p class="muted">Tip: you can bookmark this page. Token is stored in the URL as <code>?token=...</code>.</p>
@@ -976,10 +2599,11 @@ fn remote_html() -> String {
         location.href = withTokenUrl(t);
       });
 
-      async function api(path, method) {
+      async function api(path, method, body) {
         const res = await fetch(path, {
           method,
-          headers: { "X-Pomodoro-Token": token }
+          headers: { "X-Pomodoro-Token": token, "Content-Type": "application/json" },
+          body: body === undefined ? undefined : JSON.stringify(body)
         });
         if (res.status === 401) throw new Error("Unauthorized (bad token)");
         if (!res.ok) throw new Error("HTTP " + res.status);
@@ -1003,8 +2627,8 @@ fn remote_html() -> String {
         if (!token) return;
         try {
           const st = await api("/api/state", "GET");
-          document.getElementById("phase").textContent = phaseLabel(st.phase);
-          document.getElementById("time").textContent = fmt(st.remainingSeconds);
+          document.getElementById("phase").textContent = st.phaseLabel || phaseLabel(st.phase);
+          document.getElementById("time").textContent = st.inOvertime ? "+" + fmt(st.overtimeSeconds) : fmt(st.remainingSeconds);
           document.getElementById("status").textContent = st.isRunning ? "Running" : "Paused";
         } catch (e) {
           document.getElementById("status").textContent = String(e.message || e);
@@ -1017,6 +2641,15 @@ fn remote_html() -> String {
       document.getElementById("skip").addEventListener("click", async () => {
         try { await api("/api/skip", "POST"); } finally { await refresh(); }
       });
+      document.getElementById("reset").addEventListener("click", async () => {
+        try { await api("/api/reset", "POST"); } finally { await refresh(); }
+      });
+      document.getElementById("minus5").addEventListener("click", async () => {
+        try { await api("/api/adjust", "POST", { deltaSeconds: -300 }); } finally { await refresh(); }
+      });
+      document.getElementById("plus5").addEventListener("click", async () => {
+        try { await api("/api/adjust", "POST", { deltaSeconds: 300 }); } finally { await refresh(); }
+      });
 
       refresh();
       setInterval(refresh, 1000);
@@ -1185,7 +2818,7 @@ fn remote_handle_connection(app: &AppHandle, mut stream: std::net::TcpStream) {
             let next = match current {
                 Ok(st) => {
                     if st.is_running {
-                        timer_pause_inner(app, state.inner())
+                        timer_pause_inner(app, state.inner(), None)
                     } else if st.started_at.is_some() {
                         timer_resume_inner(app, state.inner(), None)
                     } else {
@@ -1210,12 +2843,15 @@ fn remote_handle_connection(app: &AppHandle, mut stream: std::net::TcpStream) {
                 }
             }
         }
-        ("POST", "/api/pause") => match timer_pause_inner(app, state.inner()) {
-            Ok(v) => serde_json::to_vec(&v).ok(),
-            Err(e) => {
-                Some(serde_json::to_vec(&serde_json::json!({ "error": e })).unwrap_or_default())
+        ("POST", "/api/pause") => {
+            let reason = serde_json::from_slice::<InterruptionReason>(&body).ok();
+            match timer_pause_inner(app, state.inner(), reason) {
+                Ok(v) => serde_json::to_vec(&v).ok(),
+                Err(e) => {
+                    Some(serde_json::to_vec(&serde_json::json!({ "error": e })).unwrap_or_default())
+                }
             }
-        },
+        }
         ("POST", "/api/resume") => {
             let payload = serde_json::from_slice::<StartTimerRequest>(&body).ok();
             match timer_resume_inner(app, state.inner(), payload) {
@@ -1231,6 +2867,24 @@ fn remote_handle_connection(app: &AppHandle, mut stream: std::net::TcpStream) {
                 Some(serde_json::to_vec(&serde_json::json!({ "error": e })).unwrap_or_default())
             }
         },
+        ("POST", "/api/reset") => match timer_reset_phase_inner(app, state.inner()) {
+            Ok(v) => serde_json::to_vec(&v).ok(),
+            Err(e) => {
+                Some(serde_json::to_vec(&serde_json::json!({ "error": e })).unwrap_or_default())
+            }
+        },
+        ("POST", "/api/adjust") => {
+            let result = match serde_json::from_slice::<AdjustTimerRequest>(&body) {
+                Ok(payload) => timer_adjust_inner(app, state.inner(), payload.delta_seconds),
+                Err(e) => Err(format!("invalid adjust payload: {e}")),
+            };
+            match result {
+                Ok(v) => serde_json::to_vec(&v).ok(),
+                Err(e) => {
+                    Some(serde_json::to_vec(&serde_json::json!({ "error": e })).unwrap_or_default())
+                }
+            }
+        }
         _ => None,
     };
 
@@ -1272,62 +2926,123 @@ fn remote_server_loop(app: AppHandle, listener: TcpListener, stop: Arc<AtomicBoo
     }
 }
 
-fn spawn_timer_worker(app: AppHandle) {
-    thread::spawn(move || loop {
-        thread::sleep(Duration::from_secs(1));
-
-        let mut should_emit = false;
-        let mut emit_state: Option<TimerState> = None;
-        let mut session_event: Option<SessionRecord> = None;
-        let mut phase_event: Option<PhaseCompletedEvent> = None;
+/// A gap between worker ticks longer than this means the app was suspended or the clock moved.
+const CLOCK_JUMP_SECONDS: i64 = 10;
 
-        {
-            let state = app.state::<AppState>();
-            let lock = state.model.lock();
-            let mut model = match lock {
-                Ok(guard) => guard,
-                Err(_) => continue,
-            };
+fn spawn_timer_worker(app: AppHandle) {
+    thread::spawn(move || {
+        // Phases that ended while the app was closed are recovered in `setup`.
+        let mut last_tick = now_ts();
+        loop {
+            thread::sleep(Duration::from_secs(1));
+
+            let now = now_ts();
+            let clock_jumped = (now - last_tick).abs() > CLOCK_JUMP_SECONDS;
+            last_tick = now;
+
+            let mut should_emit = false;
+            let mut emit_state: Option<TimerState> = None;
+            let mut session_event: Option<SessionRecord> = None;
+            let mut phase_event: Option<PhaseCompletedEvent> = None;
+            let mut recovered_event: Option<PhaseRecoveredEvent> = None;
+            let mut overtime_event = false;
 
-            if !model.timer.is_running {
-                continue;
-            }
+            {
+                let state = app.state::<AppState>();
+                let lock = state.model.lock();
+                let mut model = match lock {
+                    Ok(guard) => guard,
+                    Err(_) => continue,
+                };
+
+                if clock_jumped {
+                    match recover_expired_phase(&mut model, now) {
+                        Ok(Some(event)) => {
+                            notify_phase_recovered(&app, &model, &event);
+                            recovered_event = Some(event);
+                            emit_state = Some(model.timer.clone());
+                            should_emit = true;
+                        }
+                        Ok(None) => {}
+                        Err(error) => eprintln!("timer recovery warning: {error}"),
+                    }
+                }
 
-            let before = model.timer.remaining_seconds;
-            refresh_remaining(&mut model.timer);
+                if !model.timer.is_running {
+                    let auto_start_due = model
+                        .timer
+                        .auto_start_at
+                        .is_some_and(|auto_start_at| auto_start_at <= now_ts());
+                    if auto_start_due {
+                        begin_running(&mut model.timer);
+                        let _ = save_timer_state(&model.conn, &model.timer);
+                        emit_state = Some(model.timer.clone());
+                        should_emit = true;
+                    }
+                }
 
-            if model.timer.remaining_seconds <= 0 {
-                if let Ok((session, phase, timer)) = complete_and_advance(&app, &mut model, true) {
-                    session_event = Some(session);
-                    phase_event = Some(phase);
-                    emit_state = Some(timer);
-                    should_emit = true;
+                if model.timer.is_running {
+                    let before = model.timer.remaining_seconds;
+                    refresh_remaining(&mut model.timer);
+
+                    let flow = model.settings.flow_mode_enabled && model.timer.counts_as_focus;
+                    if model.timer.remaining_seconds <= 0 && (flow || model.timer.in_overtime) {
+                        if !model.timer.in_overtime {
+                            model.timer.in_overtime = true;
+                            notify_overtime_started(&app, &model);
+                            overtime_event = true;
+                        }
+                        refresh_remaining(&mut model.timer);
+                        let _ = save_timer_state(&model.conn, &model.timer);
+                        emit_state = Some(model.timer.clone());
+                        should_emit = true;
+                    } else if model.timer.remaining_seconds <= 0 {
+                        if let Ok((session, phase, timer)) =
+                            complete_and_advance(&app, &mut model, true)
+                        {
+                            session_event = Some(session);
+                            phase_event = Some(phase);
+                            emit_state = Some(timer);
+                            should_emit = true;
+                        }
+                    } else if model.timer.remaining_seconds != before {
+                        let _ = save_timer_state(&model.conn, &model.timer);
+                        emit_state = Some(model.timer.clone());
+                        should_emit = true;
+                    }
                 }
-            } else if model.timer.remaining_seconds != before {
-                let _ = save_timer_state(&model.conn, &model.timer);
-                emit_state = Some(model.timer.clone());
-                should_emit = true;
             }
-        }
 
-        if should_emit {
-            if let Some(session) = session_event {
-                let _ = app.emit("session://completed", &session);
-            }
-            if let Some(phase) = phase_event {
-                let _ = app.emit("timer://phase-completed", &phase);
-            }
-            if let Some(timer) = emit_state {
-                emit_timer_state(&app, &timer);
+            if should_emit {
+                if let Some(recovered) = recovered_event {
+                    let _ = app.emit("session://completed", &recovered.session);
+                    let _ = app.emit("timer://recovered", &recovered);
+                }
+                if let Some(session) = session_event {
+                    let _ = app.emit("session://completed", &session);
+                }
+                if let Some(phase) = phase_event {
+                    let _ = app.emit("timer://phase-completed", &phase);
+                }
+                if overtime_event {
+                    if let Some(timer) = &emit_state {
+                        let _ = app.emit("timer://overtime-started", timer);
+                    }
+                }
+                if let Some(timer) = emit_state {
+                    emit_timer_state(&app, &timer);
+                }
             }
         }
     });
 }
 
+// Sessions of a trashed project read as having no project until it is restored.
+const SESSION_COLUMNS: &str = "id, started_at, ended_at, phase, duration_sec, completed, interruptions,
+    (SELECT p.id FROM projects p WHERE p.id = sessions.project_id AND p.deleted_at IS NULL), counts_as_focus, profile_id, planned_duration_sec, overtime_sec, adjusted_sec, note, task_id, focus_rating, energy_level, utc_offset_sec";
+
 fn build_sessions_query(range: &AnalyticsRange) -> (String, Vec<Value>) {
-    let mut query = String::from(
-        "SELECT id, started_at, ended_at, phase, duration_sec, completed, interruptions, project_id FROM sessions WHERE 1 = 1",
-    );
+    let mut query = format!("SELECT {SESSION_COLUMNS} FROM sessions WHERE deleted_at IS NULL");
     let mut values: Vec<Value> = Vec::new();
 
     if let Some(from) = range.from {
@@ -1339,753 +3054,5227 @@ fn build_sessions_query(range: &AnalyticsRange) -> (String, Vec<Value>) {
         values.push(Value::Integer(to));
     }
     if let Some(project_id) = range.project_id {
-        query.push_str(" AND project_id = ?");
+        if range.include_subprojects {
+            query.push_str(&format!(" AND project_id IN ({PROJECT_SUBTREE_SQL})"));
+        } else {
+            query.push_str(" AND project_id = ?");
+        }
         values.push(Value::Integer(project_id));
     }
     if let Some(tag_id) = range.tag_id {
-        query.push_str(" AND EXISTS (SELECT 1 FROM session_tags st WHERE st.session_id = sessions.id AND st.tag_id = ?)");
+        query.push_str(&format!(
+            " AND EXISTS (SELECT 1 FROM session_tags st WHERE st.session_id = sessions.id AND st.tag_id IN ({TAG_SUBTREE_SQL}))"
+        ));
         values.push(Value::Integer(tag_id));
     }
+    if let Some(profile_id) = range.profile_id {
+        query.push_str(" AND profile_id = ?");
+        values.push(Value::Integer(profile_id));
+    }
+    if let Some(task_id) = range.task_id {
+        query.push_str(" AND task_id = ?");
+        values.push(Value::Integer(task_id));
+    }
 
     query.push_str(" ORDER BY ended_at DESC");
 
     (query, values)
 }
 
-fn read_session_tags(conn: &Connection, session_id: i64) -> AppResult<Vec<i64>> {
-    let mut stmt = conn
-        .prepare("SELECT tag_id FROM session_tags WHERE session_id = ?1 ORDER BY tag_id")
-        .map_err(|e| e.to_string())?;
-    let rows = stmt
-        .query_map(params![session_id], |row| row.get::<_, i64>(0))
-        .map_err(|e| e.to_string())?;
+/// Session ids bound per query, well below SQLite's limit on host parameters.
+const SESSION_ID_CHUNK: usize = 500;
 
-    let mut tags = Vec::new();
-    for row in rows {
-        tags.push(row.map_err(|e| e.to_string())?);
+/// Runs `query` for chunks of `session_ids` and groups the rows by session. The query
+/// selects the session id first and contains a `{ids}` placeholder for the id list.
+fn read_by_session_ids<T>(
+    conn: &Connection,
+    query: &str,
+    session_ids: &[i64],
+    read: impl Fn(&rusqlite::Row<'_>) -> rusqlite::Result<T>,
+) -> AppResult<HashMap<i64, Vec<T>>> {
+    let mut grouped: HashMap<i64, Vec<T>> = HashMap::new();
+    for chunk in session_ids.chunks(SESSION_ID_CHUNK) {
+        let placeholders = vec!["?"; chunk.len()].join(", ");
+        let mut stmt = conn
+            .prepare(&query.replace("{ids}", &placeholders))
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(chunk), |row| {
+                Ok((row.get::<_, i64>(0)?, read(row)?))
+            })
+            .map_err(|e| e.to_string())?;
+        for row in rows {
+            let (session_id, item) = row.map_err(|e| e.to_string())?;
+            grouped.entry(session_id).or_default().push(item);
+        }
     }
-    Ok(tags)
+    Ok(grouped)
+}
+
+fn read_session_tags(conn: &Connection, session_ids: &[i64]) -> AppResult<HashMap<i64, Vec<i64>>> {
+    read_by_session_ids(
+        conn,
+        "SELECT st.session_id, st.tag_id FROM session_tags st JOIN tags t ON t.id = st.tag_id
+         WHERE st.session_id IN ({ids}) AND t.deleted_at IS NULL ORDER BY st.tag_id",
+        session_ids,
+        |row| row.get(1),
+    )
+}
+
+fn read_session_pauses(
+    conn: &Connection,
+    session_ids: &[i64],
+) -> AppResult<HashMap<i64, Vec<SessionPause>>> {
+    read_by_session_ids(
+        conn,
+        "SELECT session_id, paused_at, resumed_at FROM session_pauses
+         WHERE session_id IN ({ids}) ORDER BY paused_at",
+        session_ids,
+        |row| {
+            Ok(SessionPause {
+                paused_at: row.get(1)?,
+                resumed_at: row.get(2)?,
+            })
+        },
+    )
+}
+
+fn interruption_kind_from_db(
+    index: usize,
+    value: Option<String>,
+) -> rusqlite::Result<Option<InterruptionKind>> {
+    value
+        .map(|raw| {
+            InterruptionKind::from_db_value(&raw).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    index,
+                    rusqlite::types::Type::Text,
+                    e.into(),
+                )
+            })
+        })
+        .transpose()
+}
+
+fn read_session_interruptions(
+    conn: &Connection,
+    session_ids: &[i64],
+) -> AppResult<HashMap<i64, Vec<InterruptionEntry>>> {
+    read_by_session_ids(
+        conn,
+        "SELECT session_id, occurred_at, kind, note, paused FROM interruption_log
+         WHERE session_id IN ({ids}) ORDER BY occurred_at, id",
+        session_ids,
+        |row| {
+            Ok(InterruptionEntry {
+                occurred_at: row.get(1)?,
+                kind: interruption_kind_from_db(2, row.get(2)?)?,
+                note: row.get(3)?,
+                paused: row.get::<_, i64>(4)? == 1,
+            })
+        },
+    )
+}
+
+/// Fills in tags, pauses and interruptions with one query each for all `sessions`.
+fn attach_session_details(conn: &Connection, sessions: &mut [SessionRecord]) -> AppResult<()> {
+    let ids: Vec<i64> = sessions.iter().map(|session| session.id).collect();
+    let mut tags = read_session_tags(conn, &ids)?;
+    let mut pauses = read_session_pauses(conn, &ids)?;
+    let mut interruptions = read_session_interruptions(conn, &ids)?;
+    for session in sessions {
+        session.tag_ids = tags.remove(&session.id).unwrap_or_default();
+        session.pauses = pauses.remove(&session.id).unwrap_or_default();
+        session.interruption_log = interruptions.remove(&session.id).unwrap_or_default();
+    }
+    Ok(())
 }
 
 fn phase_from_db(value: String) -> AppResult<TimerPhase> {
     TimerPhase::from_db_value(&value)
 }
 
-fn fetch_projects(conn: &Connection) -> AppResult<Vec<Project>> {
+/// Ids of a project and all of its live descendants, including those below a trashed
+/// subproject; takes the root id as its only parameter.
+const PROJECT_SUBTREE_SQL: &str = "WITH RECURSIVE subtree(id, live) AS (
+        SELECT ?, 1
+        UNION SELECT p.id, p.deleted_at IS NULL FROM projects p JOIN subtree ON p.parent_id = subtree.id
+    )
+    SELECT id FROM subtree WHERE live";
+
+fn project_subtree_ids(conn: &Connection, project_id: i64) -> AppResult<Vec<i64>> {
     let mut stmt = conn
-        .prepare("SELECT id, name, color, archived FROM projects ORDER BY archived ASC, name ASC")
+        .prepare(PROJECT_SUBTREE_SQL)
         .map_err(|e| e.to_string())?;
-
     let rows = stmt
-        .query_map([], |row| {
-            Ok(Project {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                color: row.get(2)?,
-                archived: row.get::<_, i64>(3)? == 1,
-            })
-        })
+        .query_map(params![project_id], |row| row.get::<_, i64>(0))
         .map_err(|e| e.to_string())?;
 
-    let mut projects = Vec::new();
+    let mut ids = Vec::new();
     for row in rows {
-        projects.push(row.map_err(|e| e.to_string())?);
+        ids.push(row.map_err(|e| e.to_string())?);
     }
-
-    Ok(projects)
+    Ok(ids)
 }
 
-fn fetch_tags(conn: &Connection) -> AppResult<Vec<Tag>> {
+/// Live projects with their paths. Subprojects of a trashed project keep pointing at it, so
+/// they are listed under its nearest live ancestor until it is restored or purged.
+fn fetch_projects(conn: &Connection) -> AppResult<Vec<Project>> {
     let mut stmt = conn
-        .prepare("SELECT id, name FROM tags ORDER BY name ASC")
+        .prepare(
+            "SELECT id, name, color, archived, parent_id, deleted_at IS NOT NULL FROM projects ORDER BY archived ASC, name ASC",
+        )
         .map_err(|e| e.to_string())?;
 
     let rows = stmt
         .query_map([], |row| {
-            Ok(Tag {
-                id: row.get(0)?,
-                name: row.get(1)?,
-            })
+            Ok((
+                Project {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    color: row.get(2)?,
+                    archived: row.get::<_, i64>(3)? == 1,
+                    parent_id: row.get(4)?,
+                    path: String::new(),
+                },
+                row.get::<_, bool>(5)?,
+            ))
         })
         .map_err(|e| e.to_string())?;
 
-    let mut tags = Vec::new();
+    let mut all = Vec::new();
     for row in rows {
-        tags.push(row.map_err(|e| e.to_string())?);
+        all.push(row.map_err(|e| e.to_string())?);
     }
 
-    Ok(tags)
+    let parents: HashMap<i64, (String, Option<i64>, bool)> = all
+        .iter()
+        .map(|(project, trashed)| {
+            (
+                project.id,
+                (project.name.clone(), project.parent_id, *trashed),
+            )
+        })
+        .collect();
+    let mut projects = Vec::new();
+    for (mut project, trashed) in all {
+        if trashed {
+            continue;
+        }
+        let mut names = vec![project.name.clone()];
+        let mut live_parent = None;
+        let mut parent_id = project.parent_id;
+        let mut steps = 0;
+        // Bounded by the project count so a corrupted cycle cannot loop forever.
+        while let Some((id, (name, next, trashed))) = parent_id
+            .and_then(|id| parents.get(&id).map(|parent| (id, parent)))
+            .filter(|_| steps < parents.len())
+        {
+            if !trashed {
+                names.push(name.clone());
+                live_parent = live_parent.or(Some(id));
+            }
+            parent_id = *next;
+            steps += 1;
+        }
+        names.reverse();
+        project.parent_id = live_parent;
+        project.path = names.join(" / ");
+        projects.push(project);
+    }
+
+    Ok(projects)
 }
 
-fn fetch_sessions(conn: &Connection, range: &AnalyticsRange) -> AppResult<Vec<SessionRecord>> {
-    let (query, values) = build_sessions_query(range);
-    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+fn fetch_project(conn: &Connection, project_id: i64) -> AppResult<Option<Project>> {
+    Ok(fetch_projects(conn)?
+        .into_iter()
+        .find(|project| project.id == project_id))
+}
+
+const TASK_SELECT: &str = "SELECT t.id, t.project_id, t.title, t.estimate_pomodoros, t.status, t.position, t.created_at, t.completed_at,
+        (SELECT COUNT(*) FROM sessions s WHERE s.task_id = t.id AND s.counts_as_focus = 1 AND s.completed = 1 AND s.deleted_at IS NULL),
+        (SELECT COALESCE(SUM(s.duration_sec), 0) FROM sessions s WHERE s.task_id = t.id AND s.counts_as_focus = 1 AND s.deleted_at IS NULL)
+     FROM tasks t";
+
+fn task_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Task> {
+    let status_raw: String = row.get(4)?;
+    let status = TaskStatus::from_db_value(&status_raw).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(4, rusqlite::types::Type::Text, e.into())
+    })?;
+    Ok(Task {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        title: row.get(2)?,
+        estimate_pomodoros: row.get(3)?,
+        status,
+        position: row.get(5)?,
+        created_at: row.get(6)?,
+        completed_at: row.get(7)?,
+        actual_pomodoros: row.get(8)?,
+        focus_seconds: row.get(9)?,
+    })
+}
+
+fn fetch_task(conn: &Connection, task_id: i64) -> AppResult<Option<Task>> {
+    conn.query_row(
+        &format!("{TASK_SELECT} WHERE t.id = ?1"),
+        params![task_id],
+        task_from_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Tasks in list order, optionally limited to one project and to unfinished tasks.
+fn fetch_tasks(
+    conn: &Connection,
+    project_id: Option<i64>,
+    include_done: bool,
+) -> AppResult<Vec<Task>> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{TASK_SELECT}
+             WHERE (?1 IS NULL OR t.project_id = ?1) AND (?2 = 1 OR t.status != 'done')
+             ORDER BY t.position ASC, t.id ASC"
+        ))
+        .map_err(|e| e.to_string())?;
 
     let rows = stmt
-        .query_map(rusqlite::params_from_iter(values), |row| {
-            Ok((
-                row.get::<_, i64>(0)?,
-                row.get::<_, i64>(1)?,
-                row.get::<_, i64>(2)?,
-                row.get::<_, String>(3)?,
-                row.get::<_, i64>(4)?,
-                row.get::<_, i64>(5)?,
-                row.get::<_, i64>(6)?,
-                row.get::<_, Option<i64>>(7)?,
-            ))
-        })
+        .query_map(params![project_id, include_done as i64], task_from_row)
         .map_err(|e| e.to_string())?;
 
-    let mut sessions = Vec::new();
+    let mut tasks = Vec::new();
     for row in rows {
-        let (
-            id,
-            started_at,
-            ended_at,
-            phase_raw,
-            duration_sec,
-            completed,
-            interruptions,
-            project_id,
-        ) = row.map_err(|e| e.to_string())?;
-        sessions.push(SessionRecord {
-            id,
-            started_at,
-            ended_at,
-            phase: phase_from_db(phase_raw)?,
-            duration_sec,
-            completed: completed == 1,
-            interruptions,
-            project_id,
-            tag_ids: read_session_tags(conn, id)?,
-        });
+        tasks.push(row.map_err(|e| e.to_string())?);
     }
-
-    Ok(sessions)
-}
-
-fn day_key(timestamp: i64) -> String {
-    let dt = Local
-        .timestamp_opt(timestamp, 0)
-        .single()
-        .unwrap_or_else(|| Local::now());
-    format!("{:04}-{:02}-{:02}", dt.year(), dt.month(), dt.day())
+    Ok(tasks)
 }
 
-fn calculate_streak_days(sessions: &[SessionRecord]) -> i64 {
-    let mut focus_days = HashSet::new();
-    for session in sessions {
-        if session.phase == TimerPhase::Focus && session.duration_sec > 0 {
-            focus_days.insert(day_key(session.ended_at));
+fn upsert_task(conn: &Connection, input: TaskInput) -> AppResult<Task> {
+    let title = input.title.trim().to_string();
+    if title.is_empty() {
+        return Err("task title cannot be empty".to_string());
+    }
+    if title.chars().count() > 200 {
+        return Err("task title can be at most 200 characters".to_string());
+    }
+    if input
+        .estimate_pomodoros
+        .is_some_and(|estimate| !(0..=100).contains(&estimate))
+    {
+        return Err("task estimate must be between 0 and 100 pomodoros".to_string());
+    }
+    if let Some(project_id) = input.project_id {
+        if !row_exists(conn, "projects", project_id)? {
+            return Err(format!("project {project_id} not found"));
         }
     }
 
-    let mut streak = 0;
-    let mut current = Local::now().date_naive();
-    loop {
-        let key = current.format("%Y-%m-%d").to_string();
-        if focus_days.contains(&key) {
-            streak += 1;
-            match current.pred_opt() {
-                Some(prev) => current = prev,
-                None => break,
-            }
-        } else {
-            break;
+    let id = if let Some(id) = input.id {
+        let updated = conn
+            .execute(
+                "UPDATE tasks
+                 SET project_id = ?1, title = ?2, estimate_pomodoros = ?3, status = ?4,
+                     completed_at = CASE WHEN ?4 = 'done' THEN COALESCE(completed_at, ?5) ELSE NULL END
+                 WHERE id = ?6",
+                params![
+                    input.project_id,
+                    title,
+                    input.estimate_pomodoros,
+                    input.status.as_db_value(),
+                    now_ts(),
+                    id
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        if updated == 0 {
+            return Err(format!("task {id} not found"));
         }
-    }
+        id
+    } else {
+        let done = input.status == TaskStatus::Done;
+        conn.execute(
+            "INSERT INTO tasks (project_id, title, estimate_pomodoros, status, position, created_at, completed_at)
+             VALUES (?1, ?2, ?3, ?4,
+                     (SELECT COALESCE(MAX(position), -1) + 1 FROM tasks WHERE project_id IS ?1),
+                     ?5, ?6)",
+            params![
+                input.project_id,
+                title,
+                input.estimate_pomodoros,
+                input.status.as_db_value(),
+                now_ts(),
+                done.then(now_ts)
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.last_insert_rowid()
+    };
 
-    streak
+    fetch_task(conn, id)?.ok_or_else(|| format!("task {id} not found"))
 }
 
-#[tauri::command]
-fn timer_start(
-    app: AppHandle,
-    state: State<'_, AppState>,
-    payload: Option<StartTimerRequest>,
-) -> AppResult<TimerState> {
-    timer_start_inner(&app, state.inner(), payload)
+fn validate_day_key(date: &str) -> AppResult<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| format!("invalid date: {date}"))
 }
 
-#[tauri::command]
-fn timer_pause(app: AppHandle, state: State<'_, AppState>) -> AppResult<TimerState> {
-    timer_pause_inner(&app, state.inner())
+/// Sessions whose analytics day is `day`, wherever they were recorded.
+fn sessions_on_day(
+    conn: &Connection,
+    day: NaiveDate,
+    day_start_min: i64,
+) -> AppResult<Vec<SessionRecord>> {
+    let midnight_utc = day
+        .and_hms_opt(0, 0, 0)
+        .map(|midnight| Utc.from_utc_datetime(&midnight).timestamp())
+        .ok_or_else(|| format!("date out of range: {day}"))?;
+    // Wide enough for any UTC offset and day start; `session_day` has the final say.
+    let sessions = fetch_sessions(
+        conn,
+        &AnalyticsRange {
+            from: Some(midnight_utc - 2 * 86_400),
+            to: Some(midnight_utc + 3 * 86_400),
+            ..Default::default()
+        },
+    )?;
+    Ok(sessions
+        .into_iter()
+        .filter(|session| session_day(session, day_start_min) == day)
+        .collect())
 }
 
-#[tauri::command]
-fn timer_resume(
-    app: AppHandle,
-    state: State<'_, AppState>,
-    payload: Option<StartTimerRequest>,
-) -> AppResult<TimerState> {
-    timer_resume_inner(&app, state.inner(), payload)
-}
+fn fetch_day_plan(conn: &Connection, date: &str) -> AppResult<Vec<DayPlanItem>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT i.id, i.plan_date, i.position, i.task_id, p.id, COALESCE(t.title, p.name),
+                    i.planned_pomodoros, i.completed_pomodoros
+             FROM day_plan_items i
+             LEFT JOIN tasks t ON t.id = i.task_id
+             LEFT JOIN projects p ON p.id = i.project_id AND p.deleted_at IS NULL
+             WHERE i.plan_date = ?1
+             ORDER BY i.position ASC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![date], |row| {
+            Ok(DayPlanItem {
+                id: row.get(0)?,
+                date: row.get(1)?,
+                position: row.get(2)?,
+                task_id: row.get(3)?,
+                project_id: row.get(4)?,
+                tag_ids: Vec::new(),
+                title: row.get(5)?,
+                planned_pomodoros: row.get(6)?,
+                completed_pomodoros: row.get(7)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
 
-#[tauri::command]
-fn timer_skip(app: AppHandle, state: State<'_, AppState>) -> AppResult<TimerState> {
-    timer_skip_inner(&app, state.inner())
+    let mut items = Vec::new();
+    for row in rows {
+        let mut item = row.map_err(|e| e.to_string())?;
+        let mut tag_stmt = conn
+            .prepare("SELECT tag_id FROM day_plan_item_tags WHERE item_id = ?1 ORDER BY tag_id")
+            .map_err(|e| e.to_string())?;
+        let tag_rows = tag_stmt
+            .query_map(params![item.id], |row| row.get::<_, i64>(0))
+            .map_err(|e| e.to_string())?;
+        for tag_id in tag_rows {
+            item.tag_ids.push(tag_id.map_err(|e| e.to_string())?);
+        }
+        items.push(item);
+    }
+    Ok(items)
 }
 
-#[tauri::command]
-fn timer_get_state(state: State<'_, AppState>) -> AppResult<TimerState> {
-    timer_get_state_inner(state.inner())
-}
+/// Replaces the queue for `date` with `items`, in order.
+fn set_day_plan(
+    conn: &mut Connection,
+    date: &str,
+    items: Vec<DayPlanItemInput>,
+) -> AppResult<Vec<DayPlanItem>> {
+    validate_day_key(date)?;
+    if items.len() > 50 {
+        return Err("a day plan can have at most 50 items".to_string());
+    }
 
-#[tauri::command]
-fn timer_set_context(
-    app: AppHandle,
-    state: State<'_, AppState>,
-    payload: StartTimerRequest,
-) -> AppResult<TimerState> {
-    let timer = {
-        let mut model = lock_model(&state)?;
-        refresh_remaining(&mut model.timer);
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let kept_ids: Vec<i64> = items.iter().filter_map(|item| item.id).collect();
+    {
+        let mut stmt = tx
+            .prepare("SELECT id FROM day_plan_items WHERE plan_date = ?1")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![date], |row| row.get::<_, i64>(0))
+            .map_err(|e| e.to_string())?;
+        for row in rows {
+            let id = row.map_err(|e| e.to_string())?;
+            if !kept_ids.contains(&id) {
+                tx.execute("DELETE FROM day_plan_items WHERE id = ?1", params![id])
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+    }
 
-        if let Some(project_id) = payload.project_id {
-            model.timer.current_project_id = project_id;
+    for (position, item) in items.into_iter().enumerate() {
+        if !(1..=24).contains(&item.planned_pomodoros) {
+            return Err("planned pomodoros must be between 1 and 24".to_string());
         }
-        if let Some(tag_ids) = payload.tag_ids {
-            model.timer.current_tag_ids = tag_ids;
+        let task = match item.task_id {
+            Some(task_id) => {
+                Some(fetch_task(&tx, task_id)?.ok_or_else(|| format!("task {task_id} not found"))?)
+            }
+            None => None,
+        };
+        let project_id = item
+            .project_id
+            .or_else(|| task.as_ref().and_then(|task| task.project_id));
+        if task.is_none() && project_id.is_none() {
+            return Err("a day plan item needs a task or a project".to_string());
+        }
+        if let Some(project_id) = project_id {
+            if !row_exists(&tx, "projects", project_id)? {
+                return Err(format!("project {project_id} not found"));
+            }
+        }
+        let tag_ids = item.tag_ids.unwrap_or_default();
+        for tag_id in &tag_ids {
+            if !row_exists(&tx, "tags", *tag_id)? {
+                return Err(format!("tag {tag_id} not found"));
+            }
         }
 
-        save_timer_state(&model.conn, &model.timer)?;
-        model.timer.clone()
-    };
-
-    emit_timer_state(&app, &timer);
-    Ok(timer)
-}
-
-#[tauri::command]
-fn session_complete(
-    payload: CompleteSessionRequest,
-    state: State<'_, AppState>,
-) -> AppResult<SessionRecord> {
-    let model = lock_model(&state)?;
-
-    model
-        .conn
-        .execute(
-            "INSERT INTO sessions (started_at, ended_at, phase, duration_sec, completed, interruptions, project_id)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-            params![
-                payload.started_at,
-                payload.ended_at,
-                payload.phase.as_db_value(),
-                payload.duration_sec,
-                payload.completed as i64,
-                payload.interruptions,
-                payload.project_id,
-            ],
-        )
-        .map_err(|e| e.to_string())?;
+        let id = if let Some(id) = item.id {
+            let updated = tx
+                .execute(
+                    "UPDATE day_plan_items
+                     SET position = ?1, task_id = ?2, project_id = ?3, planned_pomodoros = ?4
+                     WHERE id = ?5 AND plan_date = ?6",
+                    params![
+                        position as i64,
+                        item.task_id,
+                        project_id,
+                        item.planned_pomodoros,
+                        id,
+                        date
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+            if updated == 0 {
+                return Err(format!("day plan item {id} not found"));
+            }
+            tx.execute(
+                "DELETE FROM day_plan_item_tags WHERE item_id = ?1",
+                params![id],
+            )
+            .map_err(|e| e.to_string())?;
+            id
+        } else {
+            tx.execute(
+                "INSERT INTO day_plan_items (plan_date, position, task_id, project_id, planned_pomodoros)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    date,
+                    position as i64,
+                    item.task_id,
+                    project_id,
+                    item.planned_pomodoros
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            tx.last_insert_rowid()
+        };
 
-    let id = model.conn.last_insert_rowid();
-    let tag_ids = payload.tag_ids.unwrap_or_default();
-    for tag_id in &tag_ids {
-        model
-            .conn
-            .execute(
-                "INSERT OR IGNORE INTO session_tags (session_id, tag_id) VALUES (?1, ?2)",
+        for tag_id in &tag_ids {
+            tx.execute(
+                "INSERT OR IGNORE INTO day_plan_item_tags (item_id, tag_id) VALUES (?1, ?2)",
                 params![id, tag_id],
             )
             .map_err(|e| e.to_string())?;
+        }
     }
+    tx.commit().map_err(|e| e.to_string())?;
 
-    Ok(SessionRecord {
-        id,
-        started_at: payload.started_at,
-        ended_at: payload.ended_at,
-        phase: payload.phase,
-        duration_sec: payload.duration_sec,
-        completed: payload.completed,
-        interruptions: payload.interruptions,
-        project_id: payload.project_id,
-        tag_ids,
-    })
+    fetch_day_plan(conn, date)
 }
 
-#[tauri::command]
-fn analytics_get_summary(
-    range: AnalyticsRange,
-    state: State<'_, AppState>,
-) -> AppResult<AnalyticsSummary> {
-    let model = lock_model(&state)?;
-    let sessions = fetch_sessions(&model.conn, &range)?;
-
-    let mut total_focus_sec = 0;
-    let mut completed_pomodoros = 0;
-    let mut interruptions = 0;
-    let mut days_with_focus = HashSet::new();
-
-    for session in &sessions {
-        if session.phase == TimerPhase::Focus {
-            total_focus_sec += session.duration_sec;
-            interruptions += session.interruptions;
-            if session.completed {
-                completed_pomodoros += 1;
-            }
-            if session.duration_sec > 0 {
-                days_with_focus.insert(day_key(session.ended_at));
-            }
+/// Points the timer at the first unfinished day-plan item for the day of `now` and takes over
+/// its context.
+///
+/// Leaves the current context alone when the queue is empty or finished.
+fn select_day_plan_item(
+    conn: &Connection,
+    timer: &mut TimerState,
+    now: i64,
+    day_start_min: i64,
+) -> AppResult<()> {
+    let next = fetch_day_plan(conn, &day_key(now, day_start_min))?
+        .into_iter()
+        .find(|item| item.completed_pomodoros < item.planned_pomodoros);
+    match next {
+        Some(item) => {
+            timer.day_plan_item_id = Some(item.id);
+            timer.current_task_id = item.task_id;
+            timer.current_project_id = item.project_id;
+            timer.current_tag_ids = item.tag_ids;
         }
+        None => timer.day_plan_item_id = None,
     }
+    Ok(())
+}
 
-    let avg_daily_focus_sec = if days_with_focus.is_empty() {
-        0
-    } else {
-        total_focus_sec / days_with_focus.len() as i64
-    };
+/// Counts a focus phase that ended at `ended_at` against its day-plan item and queues up the
+/// next one from the day of `now`. Items planned for another day are left alone.
+fn advance_day_plan(
+    conn: &Connection,
+    timer: &mut TimerState,
+    ended_at: i64,
+    now: i64,
+    day_start_min: i64,
+) -> AppResult<()> {
+    if let Some(item_id) = timer.day_plan_item_id {
+        conn.execute(
+            "UPDATE day_plan_items SET completed_pomodoros = completed_pomodoros + 1
+             WHERE id = ?1 AND plan_date = ?2",
+            params![item_id, day_key(ended_at, day_start_min)],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    select_day_plan_item(conn, timer, now, day_start_min)
+}
 
-    Ok(AnalyticsSummary {
-        total_focus_sec,
+fn day_plan_report_for(
+    conn: &Connection,
+    date: &str,
+    day_start_min: i64,
+) -> AppResult<DayPlanReport> {
+    let day = validate_day_key(date)?;
+    let items = fetch_day_plan(conn, date)?;
+    let sessions = sessions_on_day(conn, day, day_start_min)?;
+
+    let focus_sessions = sessions.iter().filter(|session| session.counts_as_focus);
+    let focus_pomodoros = focus_sessions
+        .clone()
+        .filter(|session| session.completed)
+        .count() as i64;
+    let focus_seconds = focus_sessions.map(|session| session.duration_sec).sum();
+    let planned_pomodoros = items.iter().map(|item| item.planned_pomodoros).sum();
+    let completed_pomodoros: i64 = items.iter().map(|item| item.completed_pomodoros).sum();
+
+    Ok(DayPlanReport {
+        date: date.to_string(),
+        items,
+        planned_pomodoros,
         completed_pomodoros,
-        streak_days: calculate_streak_days(&sessions),
-        interruptions,
-        avg_daily_focus_sec,
+        focus_pomodoros,
+        unplanned_pomodoros: (focus_pomodoros - completed_pomodoros).max(0),
+        focus_seconds,
     })
 }
 
-#[tauri::command]
-fn analytics_get_timeseries(
-    range: AnalyticsRange,
-    state: State<'_, AppState>,
-) -> AppResult<Vec<TimeseriesPoint>> {
-    let model = lock_model(&state)?;
-    let sessions = fetch_sessions(&model.conn, &range)?;
-
-    let mut by_day: BTreeMap<String, TimeseriesPoint> = BTreeMap::new();
-
-    for session in sessions {
-        if session.phase != TimerPhase::Focus {
-            continue;
+/// Merges a project into `reassign_to`, moving its sessions, tasks and day-plan items,
+/// or moves it to the trash when it is `None`.
+fn remove_project(
+    conn: &mut Connection,
+    project_id: i64,
+    reassign_to: Option<i64>,
+) -> AppResult<()> {
+    if !row_exists(conn, "projects", project_id)? {
+        return Err(format!("project {project_id} not found"));
+    }
+    if let Some(target_id) = reassign_to {
+        if target_id == project_id {
+            return Err("cannot merge a project into itself".to_string());
         }
-
-        let key = day_key(session.ended_at);
-        let entry = by_day.entry(key.clone()).or_insert(TimeseriesPoint {
-            date: key,
-            focus_seconds: 0,
-            completed_pomodoros: 0,
-            interruptions: 0,
-        });
-
-        entry.focus_seconds += session.duration_sec;
-        entry.interruptions += session.interruptions;
-        if session.completed {
-            entry.completed_pomodoros += 1;
+        if !row_exists(conn, "projects", target_id)? {
+            return Err(format!("project {target_id} not found"));
         }
     }
 
-    Ok(by_day.into_values().collect())
-}
-
-#[tauri::command]
-fn projects_list(state: State<'_, AppState>) -> AppResult<Vec<Project>> {
-    let model = lock_model(&state)?;
-    fetch_projects(&model.conn)
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    if let Some(target_id) = reassign_to {
+        // Subprojects move under the merge target, or up to the merged project's parent.
+        let new_parent = if project_subtree_ids(&tx, project_id)?.contains(&target_id) {
+            tx.query_row(
+                "SELECT parent_id FROM projects WHERE id = ?1",
+                params![project_id],
+                |row| row.get::<_, Option<i64>>(0),
+            )
+            .map_err(|e| e.to_string())?
+        } else {
+            Some(target_id)
+        };
+        tx.execute(
+            "UPDATE projects SET parent_id = ?1 WHERE parent_id = ?2",
+            params![new_parent, project_id],
+        )
+        .map_err(|e| e.to_string())?;
+        for table in ["sessions", "tasks", "day_plan_items"] {
+            tx.execute(
+                &format!("UPDATE {table} SET project_id = ?1 WHERE project_id = ?2"),
+                params![target_id, project_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        tx.execute("DELETE FROM projects WHERE id = ?1", params![project_id])
+            .map_err(|e| e.to_string())?;
+    } else {
+        // Rows and subprojects keep pointing at a trashed project so restoring it brings
+        // them back; purging detaches rows through ON DELETE SET NULL.
+        tx.execute(
+            "UPDATE projects SET deleted_at = ?1 WHERE id = ?2",
+            params![now_ts(), project_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())
 }
 
-#[tauri::command]
-fn projects_upsert(input: ProjectInput, state: State<'_, AppState>) -> AppResult<Project> {
-    let model = lock_model(&state)?;
+/// Merges a tag into `reassign_to`, re-tagging its sessions and day-plan items,
+/// or moves it to the trash when it is `None`.
+fn remove_tag(conn: &mut Connection, tag_id: i64, reassign_to: Option<i64>) -> AppResult<()> {
+    if !row_exists(conn, "tags", tag_id)? {
+        return Err(format!("tag {tag_id} not found"));
+    }
+    if tag_subtree_ids(conn, tag_id)?.len() > 1 {
+        return Err("tag has nested tags; delete or merge those first".to_string());
+    }
+    if let Some(target_id) = reassign_to {
+        if target_id == tag_id {
+            return Err("cannot merge a tag into itself".to_string());
+        }
+        if !row_exists(conn, "tags", target_id)? {
+            return Err(format!("tag {target_id} not found"));
+        }
+    }
 
-    let archived = input.archived.unwrap_or(false);
-    let id = if let Some(id) = input.id {
-        model
-            .conn
-            .execute(
-                "UPDATE projects SET name = ?1, color = ?2, archived = ?3 WHERE id = ?4",
-                params![input.name, input.color, archived as i64, id],
-            )
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    if let Some(target_id) = reassign_to {
+        tx.execute(
+            "INSERT OR IGNORE INTO session_tags (session_id, tag_id)
+             SELECT session_id, ?1 FROM session_tags WHERE tag_id = ?2",
+            params![target_id, tag_id],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.execute(
+            "INSERT OR IGNORE INTO day_plan_item_tags (item_id, tag_id)
+             SELECT item_id, ?1 FROM day_plan_item_tags WHERE tag_id = ?2",
+            params![target_id, tag_id],
+        )
+        .map_err(|e| e.to_string())?;
+        // Remaining links go with the tag through ON DELETE CASCADE.
+        tx.execute("DELETE FROM tags WHERE id = ?1", params![tag_id])
             .map_err(|e| e.to_string())?;
-        id
     } else {
-        model
-            .conn
-            .execute(
-                "INSERT INTO projects (name, color, archived, created_at) VALUES (?1, ?2, ?3, ?4)",
-                params![input.name, input.color, archived as i64, now_ts()],
-            )
-            .map_err(|e| e.to_string())?;
-        model.conn.last_insert_rowid()
-    };
-
-    let project = model
-        .conn
-        .query_row(
-            "SELECT id, name, color, archived FROM projects WHERE id = ?1",
-            params![id],
-            |row| {
-                Ok(Project {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    color: row.get(2)?,
-                    archived: row.get::<_, i64>(3)? == 1,
-                })
-            },
+        tx.execute(
+            "UPDATE tags SET deleted_at = ?1 WHERE id = ?2",
+            params![now_ts(), tag_id],
         )
         .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
 
-    Ok(project)
+/// Points the timer's current project and tags away from a removed project or tag.
+fn retarget_timer_context(
+    timer: &mut TimerState,
+    project: Option<(i64, Option<i64>)>,
+    tag: Option<(i64, Option<i64>)>,
+) -> bool {
+    let mut changed = false;
+    if let Some((project_id, reassign_to)) = project {
+        if timer.current_project_id == Some(project_id) {
+            timer.current_project_id = reassign_to;
+            changed = true;
+        }
+    }
+    if let Some((tag_id, reassign_to)) = tag {
+        if timer.current_tag_ids.contains(&tag_id) {
+            timer.current_tag_ids.retain(|id| *id != tag_id);
+            if let Some(target_id) = reassign_to {
+                if !timer.current_tag_ids.contains(&target_id) {
+                    timer.current_tag_ids.push(target_id);
+                }
+            }
+            changed = true;
+        }
+    }
+    changed
 }
 
-#[tauri::command]
-fn tags_list(state: State<'_, AppState>) -> AppResult<Vec<Tag>> {
-    let model = lock_model(&state)?;
-    fetch_tags(&model.conn)
+/// Ids of a tag and every tag nested below it; takes the root id as its only parameter.
+const TAG_SUBTREE_SQL: &str = "WITH RECURSIVE subtree(id) AS (
+        SELECT ?
+        UNION SELECT t.id FROM tags t JOIN subtree ON t.parent_id = subtree.id
+        WHERE t.deleted_at IS NULL
+    )
+    SELECT id FROM subtree";
+
+fn tag_subtree_ids(conn: &Connection, tag_id: i64) -> AppResult<HashSet<i64>> {
+    let mut stmt = conn.prepare(TAG_SUBTREE_SQL).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![tag_id], |row| row.get::<_, i64>(0))
+        .map_err(|e| e.to_string())?;
+
+    let mut ids = HashSet::new();
+    for row in rows {
+        ids.insert(row.map_err(|e| e.to_string())?);
+    }
+    Ok(ids)
 }
 
-#[tauri::command]
-fn tags_upsert(input: TagInput, state: State<'_, AppState>) -> AppResult<Tag> {
-    let model = lock_model(&state)?;
+/// Trims each `/`-separated segment of a tag name, rejecting empty segments.
+fn normalize_tag_name(name: &str) -> AppResult<String> {
+    let segments: Vec<&str> = name.split('/').map(str::trim).collect();
+    if segments.iter().any(|segment| segment.is_empty()) {
+        return Err("tag names cannot have empty segments".to_string());
+    }
+    Ok(segments.join("/"))
+}
+
+fn tag_id_by_name(conn: &Connection, name: &str) -> AppResult<Option<i64>> {
+    conn.query_row(
+        "SELECT id FROM tags WHERE name = ?1 AND deleted_at IS NULL",
+        params![name],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Returns the id of the tag named `path`, creating it and any missing ancestors.
+fn ensure_tag_path(conn: &Connection, path: &str) -> AppResult<i64> {
+    if let Some(id) = tag_id_by_name(conn, path)? {
+        return Ok(id);
+    }
+    ensure_name_not_in_trash(conn, TrashKind::Tag, path)?;
+    let parent_id = match path.rsplit_once('/') {
+        Some((parent, _)) => Some(ensure_tag_path(conn, parent)?),
+        None => None,
+    };
+    conn.execute(
+        "INSERT INTO tags (name, parent_id, created_at) VALUES (?1, ?2, ?3)",
+        params![path, parent_id, now_ts()],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn upsert_tag(conn: &mut Connection, input: TagInput) -> AppResult<Tag> {
+    let name = normalize_tag_name(&input.name)?;
+    let archived = input.archived.map(i64::from);
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    ensure_name_not_in_trash(&tx, TrashKind::Tag, &name)?;
+    let parent_id = match name.rsplit_once('/') {
+        Some((parent, _)) => Some(ensure_tag_path(&tx, parent)?),
+        None => None,
+    };
 
     let id = if let Some(id) = input.id {
-        model
-            .conn
-            .execute(
-                "UPDATE tags SET name = ?1 WHERE id = ?2",
-                params![input.name, id],
+        let old_name: String = tx
+            .query_row(
+                "SELECT name FROM tags WHERE id = ?1 AND deleted_at IS NULL",
+                params![id],
+                |row| row.get(0),
             )
-            .map_err(|e| e.to_string())?;
+            .optional()
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("tag {id} not found"))?;
+        if parent_id.is_some_and(|parent_id| parent_id == id)
+            || name.starts_with(&format!("{old_name}/"))
+        {
+            return Err("a tag cannot be nested under itself".to_string());
+        }
+        tx.execute(
+            "UPDATE tags SET name = ?1, archived = COALESCE(?2, archived), parent_id = ?3
+             WHERE id = ?4",
+            params![name, archived, parent_id, id],
+        )
+        .map_err(|e| e.to_string())?;
+        // Renaming a namespace renames everything inside it.
+        tx.execute(
+            "UPDATE tags SET name = ?1 || substr(name, length(?2) + 1)
+             WHERE substr(name, 1, length(?2) + 1) = ?2 || '/'",
+            params![name, old_name],
+        )
+        .map_err(|e| e.to_string())?;
         id
     } else {
-        model
-            .conn
-            .execute(
-                "INSERT INTO tags (name, created_at) VALUES (?1, ?2)",
-                params![input.name, now_ts()],
-            )
-            .map_err(|e| e.to_string())?;
-        model.conn.last_insert_rowid()
+        tx.execute(
+            "INSERT INTO tags (name, archived, parent_id, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![name, archived.unwrap_or(0), parent_id, now_ts()],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.last_insert_rowid()
     };
 
-    let tag = model
-        .conn
+    let tag = tx
         .query_row(
-            "SELECT id, name FROM tags WHERE id = ?1",
+            "SELECT id, name, archived, parent_id FROM tags WHERE id = ?1",
             params![id],
-            |row| {
-                Ok(Tag {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                })
-            },
+            tag_from_row,
         )
         .map_err(|e| e.to_string())?;
-
+    tx.commit().map_err(|e| e.to_string())?;
     Ok(tag)
 }
 
-#[tauri::command]
-fn export_csv(range: ExportRange, state: State<'_, AppState>) -> AppResult<ExportResult> {
-    let model = lock_model(&state)?;
+/// Focus time inside a tag namespace, split by the namespace's direct children.
+///
+/// Sessions tagged with the namespace tag itself get a row of their own.
+fn tag_namespace_breakdown(
+    conn: &Connection,
+    range: &AnalyticsRange,
+    tag_id: i64,
+) -> AppResult<Vec<TagNamespaceStat>> {
+    let tags = fetch_tags(conn)?;
+    let namespace = tags
+        .iter()
+        .find(|tag| tag.id == tag_id)
+        .ok_or_else(|| format!("tag {tag_id} not found"))?;
     let sessions = fetch_sessions(
-        &model.conn,
+        conn,
         &AnalyticsRange {
             from: range.from,
             to: range.to,
-            project_id: None,
-            tag_id: None,
+            project_id: range.project_id,
+            tag_id: Some(tag_id),
+            profile_id: range.profile_id,
+            task_id: range.task_id,
+            include_subprojects: range.include_subprojects,
         },
     )?;
 
-    let mut csv = String::from(
-        "id,startedAt,endedAt,phase,durationSec,completed,interruptions,projectId,tagIds\n",
-    );
+    let mut groups = vec![(namespace, HashSet::from([tag_id]))];
+    for child in tags.iter().filter(|tag| tag.parent_id == Some(tag_id)) {
+        groups.push((child, tag_subtree_ids(conn, child.id)?));
+    }
 
-    for s in sessions {
-        let tag_ids = s
-            .tag_ids
-            .iter()
-            .map(std::string::ToString::to_string)
-            .collect::<Vec<_>>()
-            .join(";");
-        csv.push_str(&format!(
-            "{},{},{},{},{},{},{},{},{}\n",
-            s.id,
-            s.started_at,
-            s.ended_at,
-            s.phase.as_db_value(),
-            s.duration_sec,
-            s.completed,
-            s.interruptions,
-            s.project_id
-                .map(|v| v.to_string())
-                .unwrap_or_else(|| "".to_string()),
-            tag_ids
-        ));
+    let mut stats = Vec::new();
+    for (tag, ids) in groups {
+        let mut stat = TagNamespaceStat {
+            tag_id: tag.id,
+            name: tag.name.clone(),
+            focus_seconds: 0,
+            completed_pomodoros: 0,
+            sessions: 0,
+        };
+        for session in sessions.iter().filter(|session| session.counts_as_focus) {
+            if session.tag_ids.iter().any(|id| ids.contains(id)) {
+                stat.focus_seconds += session.duration_sec;
+                stat.sessions += 1;
+                if session.completed {
+                    stat.completed_pomodoros += 1;
+                }
+            }
+        }
+        if tag.id != tag_id || stat.sessions > 0 {
+            stats.push(stat);
+        }
     }
+    stats.sort_by(|a, b| {
+        b.focus_seconds
+            .cmp(&a.focus_seconds)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    Ok(stats)
+}
 
-    Ok(ExportResult {
-        filename: format!("pomodoro-sessions-{}.csv", now_ts()),
-        content: csv,
+fn tag_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Tag> {
+    Ok(Tag {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        archived: row.get::<_, i64>(2)? == 1,
+        parent_id: row.get(3)?,
     })
 }
 
-#[tauri::command]
-fn export_json(range: ExportRange, state: State<'_, AppState>) -> AppResult<ExportResult> {
-    let model = lock_model(&state)?;
+fn fetch_tags(conn: &Connection) -> AppResult<Vec<Tag>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, archived, parent_id FROM tags WHERE deleted_at IS NULL ORDER BY archived ASC, name ASC",
+        )
+        .map_err(|e| e.to_string())?;
 
-    let sessions = fetch_sessions(
-        &model.conn,
-        &AnalyticsRange {
-            from: range.from,
-            to: range.to,
-            project_id: None,
-            tag_id: None,
-        },
-    )?;
-    let projects = fetch_projects(&model.conn)?;
-    let tags = fetch_tags(&model.conn)?;
+    let rows = stmt
+        .query_map([], tag_from_row)
+        .map_err(|e| e.to_string())?;
 
-    let payload = serde_json::json!({
-      "exportedAt": now_ts(),
-      "settings": model.settings,
-      "projects": projects,
-      "tags": tags,
-      "sessions": sessions
-    });
+    let mut tags = Vec::new();
+    for row in rows {
+        tags.push(row.map_err(|e| e.to_string())?);
+    }
 
-    Ok(ExportResult {
-        filename: format!("pomodoro-backup-{}.json", now_ts()),
-        content: serde_json::to_string_pretty(&payload).map_err(|e| e.to_string())?,
+    Ok(tags)
+}
+
+/// Maps a row selected with `SESSION_COLUMNS`; tags are filled in separately.
+fn session_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SessionRecord> {
+    let phase_raw: String = row.get(3)?;
+    let phase = phase_from_db(phase_raw).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, e.into())
+    })?;
+    Ok(SessionRecord {
+        id: row.get(0)?,
+        started_at: row.get(1)?,
+        ended_at: row.get(2)?,
+        phase,
+        duration_sec: row.get(4)?,
+        completed: row.get::<_, i64>(5)? == 1,
+        interruptions: row.get(6)?,
+        project_id: row.get(7)?,
+        tag_ids: Vec::new(),
+        counts_as_focus: row.get::<_, i64>(8)? == 1,
+        profile_id: row.get(9)?,
+        planned_duration_sec: row.get(10)?,
+        overtime_sec: row.get(11)?,
+        adjusted_sec: row.get(12)?,
+        pauses: Vec::new(),
+        interruption_log: Vec::new(),
+        note: row.get(13)?,
+        task_id: row.get(14)?,
+        focus_rating: row.get(15)?,
+        energy_level: row.get(16)?,
+        utc_offset_sec: row.get(17)?,
     })
 }
 
-#[tauri::command]
-fn settings_get(state: State<'_, AppState>) -> AppResult<AppSettings> {
-    let model = lock_model(&state)?;
-    Ok(model.settings.clone())
+fn fetch_sessions(conn: &Connection, range: &AnalyticsRange) -> AppResult<Vec<SessionRecord>> {
+    let (query, values) = build_sessions_query(range);
+    fetch_sessions_by_query(conn, &query, values)
 }
 
-#[tauri::command]
-fn settings_update(
-    app: AppHandle,
-    patch: AppSettingsPatch,
-    state: State<'_, AppState>,
-) -> AppResult<AppSettings> {
-    let (settings, timer) = {
-        let mut model = lock_model(&state)?;
+/// Runs a query selecting `SESSION_COLUMNS` and fills in each session's related rows.
+fn fetch_sessions_by_query(
+    conn: &Connection,
+    query: &str,
+    values: Vec<Value>,
+) -> AppResult<Vec<SessionRecord>> {
+    let mut stmt = conn.prepare(query).map_err(|e| e.to_string())?;
 
-        if let Some(v) = patch.focus_min {
-            model.settings.focus_min = v;
-        }
-        if let Some(v) = patch.short_break_min {
-            model.settings.short_break_min = v;
-        }
-        if let Some(v) = patch.long_break_min {
-            model.settings.long_break_min = v;
-        }
-        if let Some(v) = patch.long_break_every {
-            model.settings.long_break_every = v;
-        }
-        if let Some(v) = patch.theme {
-            model.settings.theme = v.trim().to_lowercase();
-        }
-        if let Some(v) = patch.sound_enabled {
-            model.settings.sound_enabled = v;
-        }
-        if let Some(v) = patch.notifications_enabled {
-            model.settings.notifications_enabled = v;
-        }
-        if let Some(v) = patch.remote_control_enabled {
-            model.settings.remote_control_enabled = v;
-        }
-        if let Some(v) = patch.remote_control_port {
-            model.settings.remote_control_port = v;
-        }
-        if let Some(v) = patch.remote_control_token {
-            model.settings.remote_control_token = v;
-        }
+    let rows = stmt
+        .query_map(rusqlite::params_from_iter(values), session_from_row)
+        .map_err(|e| e.to_string())?;
 
-        model.settings = normalize_settings(model.settings.clone());
-        if model.settings.remote_control_token.trim().is_empty() {
-            ensure_remote_token(&mut model.settings);
-        }
-        save_json_setting(&model.conn, APP_SETTINGS_KEY, &model.settings)?;
+    let mut sessions = Vec::new();
+    for row in rows {
+        sessions.push(row.map_err(|e| e.to_string())?);
+    }
+    attach_session_details(conn, &mut sessions)?;
 
-        // Keep the current phase duration in sync if timer is idle.
-        if !model.timer.is_running {
-            model.timer.phase_total_seconds = model
-                .settings
-                .duration_for_phase_seconds(&model.timer.phase);
-            model.timer.remaining_seconds = model.timer.phase_total_seconds;
-            model.timer.started_at = None;
-            model.timer.target_ends_at = None;
-            save_timer_state(&model.conn, &model.timer)?;
-        }
+    Ok(sessions)
+}
 
-        (model.settings.clone(), model.timer.clone())
-    };
+/// Turns free text into an FTS5 query matching every word as a prefix, so user input
+/// can never be parsed as FTS operators.
+fn fts_match_query(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
 
-    // Start/stop/restart remote control server based on settings.
-    remote_apply(&app, &settings)?;
+/// Sessions in `range` whose note matches `text`, best matches first.
+fn search_sessions(
+    conn: &Connection,
+    text: &str,
+    range: &AnalyticsRange,
+) -> AppResult<Vec<SessionRecord>> {
+    let Some(match_query) = fts_match_query(text) else {
+        return fetch_sessions(conn, range);
+    };
+    let (sessions_query, mut values) = build_sessions_query(range);
+    let query = format!(
+        "SELECT s.* FROM ({sessions_query}) s
+         JOIN session_notes_fts ON session_notes_fts.rowid = s.id
+         WHERE session_notes_fts MATCH ?
+         ORDER BY session_notes_fts.rank, s.ended_at DESC"
+    );
+    values.push(Value::Text(match_query));
+    fetch_sessions_by_query(conn, &query, values)
+}
+
+fn fetch_session(conn: &Connection, session_id: i64) -> AppResult<Option<SessionRecord>> {
+    let session = conn
+        .query_row(
+            &format!("SELECT {SESSION_COLUMNS} FROM sessions WHERE id = ?1 AND deleted_at IS NULL"),
+            params![session_id],
+            session_from_row,
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let Some(session) = session else {
+        return Ok(None);
+    };
+    let mut sessions = [session];
+    attach_session_details(conn, &mut sessions)?;
+    let [session] = sessions;
+    Ok(Some(session))
+}
+
+/// Clock skew tolerated before a manually logged session counts as ending in the future.
+const SESSION_FUTURE_SKEW_SECONDS: i64 = 60;
+
+fn validate_session_times(
+    started_at: i64,
+    ended_at: i64,
+    duration_sec: i64,
+) -> Result<(), SessionError> {
+    if ended_at < started_at {
+        return Err(SessionError::invalid(
+            "endedAt",
+            "session cannot end before it starts",
+        ));
+    }
+    if duration_sec < 0 {
+        return Err(SessionError::invalid(
+            "durationSec",
+            "session duration cannot be negative",
+        ));
+    }
+    if duration_sec > ended_at - started_at {
+        return Err(SessionError::invalid(
+            "durationSec",
+            "session duration cannot exceed the time between start and end",
+        ));
+    }
+    Ok(())
+}
+
+/// Whether `table` has a row with `id`; rows in the trash count as missing.
+fn row_exists(conn: &Connection, table: &str, id: i64) -> AppResult<bool> {
+    let live = if ["sessions", "projects", "tags"].contains(&table) {
+        " AND deleted_at IS NULL"
+    } else {
+        ""
+    };
+    conn.query_row(
+        &format!("SELECT EXISTS(SELECT 1 FROM {table} WHERE id = ?1{live})"),
+        params![id],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn ensure_project_exists(conn: &Connection, project_id: i64) -> Result<(), SessionError> {
+    if !row_exists(conn, "projects", project_id)? {
+        return Err(SessionError::not_found(
+            "projectId",
+            format!("project {project_id} not found"),
+        ));
+    }
+    Ok(())
+}
+
+fn ensure_tags_exist(conn: &Connection, tag_ids: &[i64]) -> Result<(), SessionError> {
+    for tag_id in tag_ids {
+        if !row_exists(conn, "tags", *tag_id)? {
+            return Err(SessionError::not_found(
+                "tagIds",
+                format!("tag {tag_id} not found"),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Ids of sessions sharing any time with `[started_at, ended_at)`, optionally ignoring one.
+fn overlapping_session_ids(
+    conn: &Connection,
+    started_at: i64,
+    ended_at: i64,
+    exclude_id: Option<i64>,
+) -> AppResult<Vec<i64>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id FROM sessions
+             WHERE started_at < ?1 AND ended_at > ?2 AND (?3 IS NULL OR id != ?3) AND deleted_at IS NULL
+             ORDER BY started_at",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![ended_at, started_at, exclude_id], |row| {
+            row.get::<_, i64>(0)
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut ids = Vec::new();
+    for row in rows {
+        ids.push(row.map_err(|e| e.to_string())?);
+    }
+    Ok(ids)
+}
+
+/// Shortens `session` to the part outside `[started_at, ended_at)`, deleting it when nothing is left.
+///
+/// The caller rejects sessions that surround the new one, which would need a split.
+fn trim_session_around(
+    conn: &Connection,
+    session: &SessionRecord,
+    started_at: i64,
+    ended_at: i64,
+) -> AppResult<()> {
+    let (new_start, new_end) = if session.started_at < started_at {
+        (session.started_at, started_at)
+    } else if session.ended_at > ended_at {
+        (ended_at, session.ended_at)
+    } else {
+        return trash_session(conn, session.id, now_ts());
+    };
+
+    let new_span = new_end - new_start;
+    let span_change = new_span - (session.ended_at - session.started_at);
+    let duration_sec = (session.duration_sec + span_change).clamp(0, new_span);
+    conn.execute(
+        "UPDATE sessions SET started_at = ?1, ended_at = ?2, duration_sec = ?3, overtime_sec = MIN(overtime_sec, ?3)
+         WHERE id = ?4",
+        params![new_start, new_end, duration_sec, session.id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn validate_session_entry(
+    conn: &Connection,
+    payload: &CompleteSessionRequest,
+    now: i64,
+) -> Result<(), SessionError> {
+    if payload.started_at <= 0 {
+        return Err(SessionError::invalid(
+            "startedAt",
+            "session start must be a valid timestamp",
+        ));
+    }
+    if payload.ended_at > now + SESSION_FUTURE_SKEW_SECONDS {
+        return Err(SessionError::invalid(
+            "endedAt",
+            "session cannot end in the future",
+        ));
+    }
+    validate_session_times(payload.started_at, payload.ended_at, payload.duration_sec)?;
+    if payload.interruptions < 0 {
+        return Err(SessionError::invalid(
+            "interruptions",
+            "interruptions cannot be negative",
+        ));
+    }
+    if let Some(overtime_sec) = payload.overtime_sec {
+        if !(0..=payload.duration_sec).contains(&overtime_sec) {
+            return Err(SessionError::invalid(
+                "overtimeSec",
+                "overtime must be between zero and the session duration",
+            ));
+        }
+    }
+    if payload
+        .planned_duration_sec
+        .is_some_and(|planned| !(0..=MAX_PHASE_SECONDS).contains(&planned))
+    {
+        return Err(SessionError::invalid(
+            "plannedDurationSec",
+            "planned duration must be between zero and four hours",
+        ));
+    }
+    if payload
+        .adjusted_sec
+        .is_some_and(|adjusted| adjusted.abs() > MAX_PHASE_SECONDS)
+    {
+        return Err(SessionError::invalid(
+            "adjustedSec",
+            "adjustment cannot exceed four hours",
+        ));
+    }
+    if let Some(project_id) = payload.project_id {
+        ensure_project_exists(conn, project_id)?;
+    }
+    if let Some(tag_ids) = &payload.tag_ids {
+        ensure_tags_exist(conn, tag_ids)?;
+    }
+    if let Some(task_id) = payload.task_id {
+        if !row_exists(conn, "tasks", task_id)? {
+            return Err(SessionError::not_found(
+                "taskId",
+                format!("task {task_id} not found"),
+            ));
+        }
+    }
+    if let Some(profile_id) = payload.profile_id {
+        if !row_exists(conn, "timer_profiles", profile_id)? {
+            return Err(SessionError::not_found(
+                "profileId",
+                format!("timer profile {profile_id} not found"),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Logs a past session after validating it and resolving overlaps per `payload.overlap`.
+fn log_session(
+    conn: &mut Connection,
+    payload: CompleteSessionRequest,
+    now: i64,
+) -> Result<SessionRecord, SessionError> {
+    validate_session_entry(conn, &payload, now)?;
+
+    let conflicts = overlapping_session_ids(conn, payload.started_at, payload.ended_at, None)?;
+    if !conflicts.is_empty() && payload.overlap == OverlapPolicy::Reject {
+        return Err(SessionError::overlap(conflicts));
+    }
+    if payload.overlap == OverlapPolicy::Trim {
+        let mut surrounding = Vec::new();
+        for session_id in &conflicts {
+            if let Some(session) = fetch_session(conn, *session_id)? {
+                if session.started_at < payload.started_at && session.ended_at > payload.ended_at {
+                    surrounding.push(session.id);
+                }
+            }
+        }
+        if !surrounding.is_empty() {
+            return Err(SessionError {
+                message: "session lies inside an existing session; trimming would split it"
+                    .to_string(),
+                ..SessionError::overlap(surrounding)
+            });
+        }
+    }
+
+    let counts_as_focus = payload
+        .counts_as_focus
+        .unwrap_or(payload.phase == TimerPhase::Focus);
+    let overtime_sec = payload.overtime_sec.unwrap_or(0);
+    let adjusted_sec = payload.adjusted_sec.unwrap_or(0);
+    let planned_duration_sec = payload
+        .planned_duration_sec
+        .unwrap_or(payload.duration_sec - overtime_sec);
+    let note = normalize_session_note(payload.note);
+    let mut tag_ids = payload.tag_ids.unwrap_or_default();
+    tag_ids.sort_unstable();
+    tag_ids.dedup();
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for session_id in &conflicts {
+        match payload.overlap {
+            OverlapPolicy::Accept | OverlapPolicy::Reject => {}
+            OverlapPolicy::Trim => {
+                if let Some(session) = fetch_session(&tx, *session_id)? {
+                    trim_session_around(&tx, &session, payload.started_at, payload.ended_at)?;
+                }
+            }
+            OverlapPolicy::Replace => trash_session(&tx, *session_id, now)?,
+        }
+    }
+
+    let utc_offset_sec = local_utc_offset(payload.ended_at);
+    tx.execute(
+        "INSERT INTO sessions (started_at, ended_at, phase, duration_sec, completed, interruptions, project_id, counts_as_focus, profile_id, planned_duration_sec, overtime_sec, adjusted_sec, note, task_id, utc_offset_sec)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+        params![
+            payload.started_at,
+            payload.ended_at,
+            payload.phase.as_db_value(),
+            payload.duration_sec,
+            payload.completed as i64,
+            payload.interruptions,
+            payload.project_id,
+            counts_as_focus as i64,
+            payload.profile_id,
+            planned_duration_sec,
+            overtime_sec,
+            adjusted_sec,
+            note,
+            payload.task_id,
+            utc_offset_sec,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let id = tx.last_insert_rowid();
+    for tag_id in &tag_ids {
+        tx.execute(
+            "INSERT OR IGNORE INTO session_tags (session_id, tag_id) VALUES (?1, ?2)",
+            params![id, tag_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(SessionRecord {
+        id,
+        started_at: payload.started_at,
+        ended_at: payload.ended_at,
+        phase: payload.phase,
+        duration_sec: payload.duration_sec,
+        completed: payload.completed,
+        interruptions: payload.interruptions,
+        project_id: payload.project_id,
+        tag_ids,
+        counts_as_focus,
+        profile_id: payload.profile_id,
+        planned_duration_sec,
+        overtime_sec,
+        adjusted_sec,
+        pauses: Vec::new(),
+        interruption_log: Vec::new(),
+        note,
+        task_id: payload.task_id,
+        focus_rating: None,
+        energy_level: None,
+        utc_offset_sec: Some(utc_offset_sec),
+    })
+}
+
+fn update_session(
+    conn: &mut Connection,
+    request: UpdateSessionRequest,
+) -> Result<SessionRecord, SessionError> {
+    let existing = fetch_session(conn, request.id)?.ok_or_else(|| {
+        SessionError::not_found("id", format!("session {} not found", request.id))
+    })?;
+
+    let started_at = request.started_at.unwrap_or(existing.started_at);
+    let ended_at = request.ended_at.unwrap_or(existing.ended_at);
+    let duration_sec = request.duration_sec.unwrap_or_else(|| {
+        let span_change = (ended_at - started_at) - (existing.ended_at - existing.started_at);
+        (existing.duration_sec + span_change).clamp(0, (ended_at - started_at).max(0))
+    });
+    validate_session_times(started_at, ended_at, duration_sec)?;
+    if started_at != existing.started_at || ended_at != existing.ended_at {
+        let conflicts = overlapping_session_ids(conn, started_at, ended_at, Some(existing.id))?;
+        if !conflicts.is_empty() {
+            return Err(SessionError::overlap(conflicts));
+        }
+    }
+
+    let phase_changed = request
+        .phase
+        .as_ref()
+        .is_some_and(|phase| *phase != existing.phase);
+    let phase = request.phase.unwrap_or(existing.phase);
+    let counts_as_focus = request.counts_as_focus.unwrap_or(if phase_changed {
+        phase == TimerPhase::Focus
+    } else {
+        existing.counts_as_focus
+    });
+    // Like recorded sessions, only focus keeps a project, task and tags; a session turned
+    // into a break drops the ones it had.
+    if !counts_as_focus {
+        if request
+            .project_id
+            .is_some_and(|project_id| project_id.is_some())
+        {
+            return Err(SessionError::invalid(
+                "projectId",
+                "only focus sessions can have a project",
+            ));
+        }
+        if request.task_id.is_some_and(|task_id| task_id.is_some()) {
+            return Err(SessionError::invalid(
+                "taskId",
+                "only focus sessions can have a task",
+            ));
+        }
+        if request
+            .tag_ids
+            .as_ref()
+            .is_some_and(|tag_ids| !tag_ids.is_empty())
+        {
+            return Err(SessionError::invalid(
+                "tagIds",
+                "only focus sessions can have tags",
+            ));
+        }
+    }
+    let project_id = request
+        .project_id
+        .unwrap_or(existing.project_id)
+        .filter(|_| counts_as_focus);
+    if let Some(project_id) = project_id {
+        ensure_project_exists(conn, project_id)?;
+    }
+    let tag_ids = if counts_as_focus {
+        request.tag_ids.unwrap_or(existing.tag_ids)
+    } else {
+        Vec::new()
+    };
+    ensure_tags_exist(conn, &tag_ids)?;
+    let completed = request.completed.unwrap_or(existing.completed);
+    let overtime_sec = existing.overtime_sec.min(duration_sec);
+    let note = match request.note {
+        Some(note) => normalize_session_note(note),
+        None => existing.note,
+    };
+    let task_id = request
+        .task_id
+        .unwrap_or(existing.task_id)
+        .filter(|_| counts_as_focus);
+    if let Some(task_id) = task_id {
+        if !row_exists(conn, "tasks", task_id)? {
+            return Err(SessionError::not_found(
+                "taskId",
+                format!("task {task_id} not found"),
+            ));
+        }
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "UPDATE sessions SET started_at = ?1, ended_at = ?2, phase = ?3, duration_sec = ?4, completed = ?5,
+             project_id = ?6, counts_as_focus = ?7, overtime_sec = ?8, note = ?9, task_id = ?10
+         WHERE id = ?11",
+        params![
+            started_at,
+            ended_at,
+            phase.as_db_value(),
+            duration_sec,
+            completed as i64,
+            project_id,
+            counts_as_focus as i64,
+            overtime_sec,
+            note,
+            task_id,
+            request.id,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.execute(
+        "DELETE FROM session_tags WHERE session_id = ?1",
+        params![request.id],
+    )
+    .map_err(|e| e.to_string())?;
+    for tag_id in &tag_ids {
+        tx.execute(
+            "INSERT OR IGNORE INTO session_tags (session_id, tag_id) VALUES (?1, ?2)",
+            params![request.id, tag_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    fetch_session(conn, request.id)?
+        .ok_or_else(|| SessionError::not_found("id", format!("session {} not found", request.id)))
+}
+
+/// Moves the given sessions to the trash; returns how many were live.
+fn delete_sessions(conn: &mut Connection, session_ids: &[i64]) -> AppResult<usize> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let now = now_ts();
+    let mut deleted = 0;
+    for session_id in session_ids {
+        deleted += tx
+            .execute(
+                "UPDATE sessions SET deleted_at = ?2 WHERE id = ?1 AND deleted_at IS NULL",
+                params![session_id, now],
+            )
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(deleted)
+}
+
+fn trash_session(conn: &Connection, session_id: i64, now: i64) -> AppResult<()> {
+    conn.execute(
+        "UPDATE sessions SET deleted_at = ?2 WHERE id = ?1 AND deleted_at IS NULL",
+        params![session_id, now],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Rejects a name that is still held by a trashed project or tag.
+fn ensure_name_not_in_trash(conn: &Connection, kind: TrashKind, name: &str) -> AppResult<()> {
+    let trashed: bool = conn
+        .query_row(
+            &format!(
+                "SELECT EXISTS(SELECT 1 FROM {} WHERE name = ?1 AND deleted_at IS NOT NULL)",
+                kind.table()
+            ),
+            params![name],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if trashed {
+        let noun = match kind {
+            TrashKind::Project => "project",
+            _ => "tag",
+        };
+        return Err(format!(
+            "a {noun} named \"{name}\" is in the trash; restore or purge it first"
+        ));
+    }
+    Ok(())
+}
+
+fn fetch_trash(conn: &Connection) -> AppResult<Vec<TrashItem>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT 'session', id, phase, started_at, deleted_at FROM sessions WHERE deleted_at IS NOT NULL
+             UNION ALL SELECT 'project', id, name, NULL, deleted_at FROM projects WHERE deleted_at IS NOT NULL
+             UNION ALL SELECT 'tag', id, name, NULL, deleted_at FROM tags WHERE deleted_at IS NOT NULL
+             ORDER BY 5 DESC, 2 DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<i64>>(3)?,
+                row.get::<_, i64>(4)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut items = Vec::new();
+    for row in rows {
+        let (kind, id, label, started_at, deleted_at) = row.map_err(|e| e.to_string())?;
+        let (kind, label) = match kind.as_str() {
+            "session" => (
+                TrashKind::Session,
+                TimerPhase::from_db_value(&label)?.to_string(),
+            ),
+            "project" => (TrashKind::Project, label),
+            _ => (TrashKind::Tag, label),
+        };
+        items.push(TrashItem {
+            kind,
+            id,
+            label,
+            started_at,
+            deleted_at,
+        });
+    }
+    Ok(items)
+}
+
+/// Takes an item out of the trash. Projects and tags bring their trashed ancestors back too.
+fn restore_trashed(conn: &mut Connection, kind: TrashKind, id: i64) -> AppResult<()> {
+    let table = kind.table();
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let restored = tx
+        .execute(
+            &format!(
+                "UPDATE {table} SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL"
+            ),
+            params![id],
+        )
+        .map_err(|e| e.to_string())?;
+    if restored == 0 {
+        return Err(format!("{table} item {id} is not in the trash"));
+    }
+    if kind != TrashKind::Session {
+        tx.execute(
+            &format!(
+                "WITH RECURSIVE ancestors(id) AS (
+                    SELECT parent_id FROM {table} WHERE id = ?1
+                    UNION SELECT t.parent_id FROM {table} t JOIN ancestors a ON t.id = a.id
+                )
+                UPDATE {table} SET deleted_at = NULL WHERE id IN (SELECT id FROM ancestors)"
+            ),
+            params![id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Moves the subprojects of trashed projects about to be purged up to their nearest
+/// ancestor that stays, which is what the tree looked like while they were trashed.
+fn reparent_purged_subprojects(
+    conn: &Connection,
+    project_id: Option<i64>,
+    deleted_before: Option<i64>,
+) -> AppResult<()> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, parent_id, deleted_at IS NOT NULL AND (?1 IS NULL OR id = ?1) AND (?2 IS NULL OR deleted_at < ?2)
+             FROM projects",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![project_id, deleted_before], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                (row.get::<_, Option<i64>>(1)?, row.get::<_, bool>(2)?),
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    let projects = rows
+        .collect::<rusqlite::Result<HashMap<_, _>>>()
+        .map_err(|e| e.to_string())?;
+
+    for (id, (parent_id, purged)) in &projects {
+        let is_purged = |id: &i64| projects.get(id).is_some_and(|(_, purged)| *purged);
+        if *purged || !parent_id.as_ref().is_some_and(is_purged) {
+            continue;
+        }
+        let mut ancestor = *parent_id;
+        let mut steps = 0;
+        while let Some(purged_id) = ancestor
+            .filter(is_purged)
+            .filter(|_| steps < projects.len())
+        {
+            ancestor = projects[&purged_id].0;
+            steps += 1;
+        }
+        conn.execute(
+            "UPDATE projects SET parent_id = ?1 WHERE id = ?2",
+            params![ancestor, id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Permanently deletes trashed items: one item, everything, or whatever was trashed before `deleted_before`.
+fn purge_trash(
+    conn: &mut Connection,
+    item: Option<(TrashKind, i64)>,
+    deleted_before: Option<i64>,
+) -> AppResult<usize> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    if item.is_none_or(|(kind, _)| kind == TrashKind::Project) {
+        reparent_purged_subprojects(&tx, item.map(|(_, id)| id), deleted_before)?;
+    }
+    let mut purged = 0;
+    for kind in [TrashKind::Session, TrashKind::Tag, TrashKind::Project] {
+        let id = match item {
+            Some((item_kind, _)) if item_kind != kind => continue,
+            Some((_, id)) => Some(id),
+            None => None,
+        };
+        // Foreign keys detach purged projects from sessions and drop purged tags' links.
+        purged += tx
+            .execute(
+                &format!(
+                    "DELETE FROM {} WHERE deleted_at IS NOT NULL AND (?1 IS NULL OR id = ?1) AND (?2 IS NULL OR deleted_at < ?2)",
+                    kind.table()
+                ),
+                params![id, deleted_before],
+            )
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(purged)
+}
+
+fn purge_expired_trash(
+    conn: &mut Connection,
+    settings: &AppSettings,
+    now: i64,
+) -> AppResult<usize> {
+    if settings.trash_retention_days == 0 {
+        return Ok(0);
+    }
+    purge_trash(
+        conn,
+        None,
+        Some(now - settings.trash_retention_days * 86_400),
+    )
+}
+
+/// Tables holding user data, parents before children.
+const USER_DATA_TABLES: &[&str] = &[
+    "settings",
+    "timer_profiles",
+    "cycle_plans",
+    "cycle_plan_steps",
+    "projects",
+    "tags",
+    "tasks",
+    "day_plan_items",
+    "day_plan_item_tags",
+    "sessions",
+    "session_tags",
+    "session_pauses",
+    "interruption_log",
+];
+
+fn reset_snapshot_path(conn: &Connection) -> AppResult<PathBuf> {
+    match conn.path() {
+        Some(path) if !path.is_empty() => Ok(PathBuf::from(format!("{path}.pre-reset"))),
+        _ => Err("database has no file to snapshot".to_string()),
+    }
+}
+
+/// Refuses to undo a reset once anything was written after it, since the restore would drop it.
+fn ensure_nothing_since_reset(
+    conn: &Connection,
+    snapshot: &ResetSnapshot,
+    settings: &AppSettings,
+) -> AppResult<()> {
+    if serde_json::to_value(settings).map_err(|e| e.to_string())? != snapshot.settings {
+        return Err("settings changed since the reset; undoing it would lose them".to_string());
+    }
+    for table in USER_DATA_TABLES
+        .iter()
+        .filter(|table| **table != "settings")
+    {
+        let has_rows: bool = conn
+            .query_row(
+                &format!("SELECT EXISTS (SELECT 1 FROM {table})"),
+                [],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if has_rows {
+            return Err(format!(
+                "new {table} rows were added since the reset; undoing it would lose them"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Replaces every user data table with its contents in the snapshot at `path`.
+fn restore_snapshot(conn: &Connection, path: &Path) -> AppResult<()> {
+    conn.execute(
+        "ATTACH DATABASE ?1 AS snapshot",
+        params![path.to_string_lossy()],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let restore = || -> AppResult<()> {
+        let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+        tx.execute_batch("PRAGMA defer_foreign_keys = ON;")
+            .map_err(|e| e.to_string())?;
+        for table in USER_DATA_TABLES.iter().rev() {
+            tx.execute(&format!("DELETE FROM main.{table}"), [])
+                .map_err(|e| e.to_string())?;
+        }
+        for table in USER_DATA_TABLES.iter().chain(&["sqlite_sequence"]) {
+            if *table == "sqlite_sequence" {
+                tx.execute("DELETE FROM main.sqlite_sequence", [])
+                    .map_err(|e| e.to_string())?;
+            }
+            tx.execute(
+                &format!("INSERT INTO main.{table} SELECT * FROM snapshot.{table}"),
+                [],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        tx.commit().map_err(|e| e.to_string())
+    };
+    let result = restore();
+
+    conn.execute_batch("DETACH DATABASE snapshot;")
+        .map_err(|e| e.to_string())?;
+    result
+}
+
+fn fetch_cycle_plan(conn: &Connection, plan_id: i64) -> AppResult<Option<CyclePlan>> {
+    let name: Option<String> = conn
+        .query_row(
+            "SELECT name FROM cycle_plans WHERE id = ?1",
+            params![plan_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let Some(name) = name else {
+        return Ok(None);
+    };
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT phase, label, duration_min, counts_as_focus FROM cycle_plan_steps
+             WHERE plan_id = ?1 ORDER BY position ASC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![plan_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, i64>(3)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut steps = Vec::new();
+    for row in rows {
+        let (phase_raw, label, duration_min, counts_as_focus) = row.map_err(|e| e.to_string())?;
+        steps.push(CyclePlanStep {
+            phase: phase_from_db(phase_raw)?,
+            label,
+            duration_min,
+            counts_as_focus: counts_as_focus == 1,
+        });
+    }
+
+    Ok(Some(CyclePlan {
+        id: plan_id,
+        name,
+        steps,
+    }))
+}
+
+fn fetch_cycle_plans(conn: &Connection) -> AppResult<Vec<CyclePlan>> {
+    let mut stmt = conn
+        .prepare("SELECT id FROM cycle_plans ORDER BY name ASC")
+        .map_err(|e| e.to_string())?;
+    let ids = stmt
+        .query_map([], |row| row.get::<_, i64>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut plans = Vec::new();
+    for id in ids {
+        if let Some(plan) = fetch_cycle_plan(conn, id)? {
+            plans.push(plan);
+        }
+    }
+    Ok(plans)
+}
+
+fn timer_profile_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<TimerProfile> {
+    Ok(TimerProfile {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        focus_min: row.get(2)?,
+        short_break_min: row.get(3)?,
+        long_break_min: row.get(4)?,
+        long_break_every: row.get(5)?,
+    })
+}
+
+fn fetch_timer_profile(conn: &Connection, profile_id: i64) -> AppResult<Option<TimerProfile>> {
+    conn.query_row(
+        "SELECT id, name, focus_min, short_break_min, long_break_min, long_break_every
+         FROM timer_profiles WHERE id = ?1",
+        params![profile_id],
+        timer_profile_from_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+fn fetch_timer_profiles(conn: &Connection) -> AppResult<Vec<TimerProfile>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, focus_min, short_break_min, long_break_min, long_break_every
+             FROM timer_profiles ORDER BY name ASC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([], timer_profile_from_row)
+        .map_err(|e| e.to_string())?;
+
+    let mut profiles = Vec::new();
+    for row in rows {
+        profiles.push(row.map_err(|e| e.to_string())?);
+    }
+
+    Ok(profiles)
+}
+
+fn normalize_plan_steps(steps: Vec<CyclePlanStep>) -> AppResult<Vec<CyclePlanStep>> {
+    if steps.is_empty() {
+        return Err("a cycle plan needs at least one step".to_string());
+    }
+    if steps.len() > 50 {
+        return Err("a cycle plan can have at most 50 steps".to_string());
+    }
+
+    Ok(steps
+        .into_iter()
+        .map(|mut step| {
+            let label = step.label.trim();
+            step.label = if label.is_empty() {
+                step.phase.to_string()
+            } else {
+                label.to_string()
+            };
+            step.duration_min = step.duration_min.clamp(1, 180);
+            // Built-in kinds keep their meaning; only custom kinds choose.
+            step.counts_as_focus = match step.phase {
+                TimerPhase::Focus => true,
+                TimerPhase::ShortBreak | TimerPhase::LongBreak => false,
+                TimerPhase::Custom(_) => step.counts_as_focus,
+            };
+            step
+        })
+        .collect())
+}
+
+/// The analytics day of `timestamp` on this machine as "YYYY-MM-DD", the key of day plans.
+fn day_key(timestamp: i64, day_start_min: i64) -> String {
+    analytics_day(timestamp, None, day_start_min)
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+fn local_utc_offset(timestamp: i64) -> i32 {
+    Local
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .map(|time| time.offset().fix().local_minus_utc())
+        .unwrap_or(0)
+}
+
+/// `utc_offset_sec`, or this machine's offset at `timestamp` when it is unknown.
+fn resolve_utc_offset(timestamp: i64, utc_offset_sec: Option<i32>) -> FixedOffset {
+    FixedOffset::east_opt(utc_offset_sec.unwrap_or_else(|| local_utc_offset(timestamp)))
+        .unwrap_or_else(|| Utc.fix())
+}
+
+/// The analytics day `timestamp` belongs to: local time at `utc_offset_sec` (or this machine's
+/// zone when unknown), shifted back by the configured day start.
+fn analytics_day(timestamp: i64, utc_offset_sec: Option<i32>, day_start_min: i64) -> NaiveDate {
+    let offset = resolve_utc_offset(timestamp, utc_offset_sec);
+    let local = offset
+        .timestamp_opt(timestamp - day_start_min * 60, 0)
+        .single()
+        .unwrap_or_else(|| Utc::now().with_timezone(&offset));
+    local.date_naive()
+}
+
+fn session_day(session: &SessionRecord, day_start_min: i64) -> NaiveDate {
+    analytics_day(session.ended_at, session.utc_offset_sec, day_start_min)
+}
+
+/// Streaks of days meeting the daily goal, up to and including `today`.
+///
+/// Rest weekdays and vacation dates without a met goal neither count nor break a streak;
+/// a rest day that meets the goal counts like any other.
+fn calculate_streaks(
+    sessions: &[SessionRecord],
+    settings: &AppSettings,
+    today: NaiveDate,
+) -> StreakStats {
+    let day_start_min = settings.day_start_minutes();
+    let mut days: HashMap<NaiveDate, (i64, i64)> = HashMap::new();
+    for session in sessions {
+        if session.counts_as_focus && session.duration_sec > 0 {
+            let entry = days.entry(session_day(session, day_start_min)).or_default();
+            entry.0 += session.duration_sec;
+            if session.completed {
+                entry.1 += 1;
+            }
+        }
+    }
+    let goal_met = |day: &NaiveDate| match days.get(day) {
+        Some((focus_seconds, _)) if settings.daily_goal_pomodoros == 0 => *focus_seconds > 0,
+        Some((_, completed)) => *completed >= settings.daily_goal_pomodoros,
+        None => false,
+    };
+    let vacation: HashSet<String> = settings.vacation_dates.iter().cloned().collect();
+    let excused = |day: &NaiveDate| {
+        settings
+            .rest_weekdays
+            .contains(&day.weekday().num_days_from_monday())
+            || vacation.contains(&day.format("%Y-%m-%d").to_string())
+    };
+
+    let mut stats = StreakStats {
+        current_days: 0,
+        longest_days: 0,
+        longest_start: None,
+        longest_end: None,
+        today_goal_met: goal_met(&today),
+    };
+    let Some(first) = days.keys().copied().filter(|day| goal_met(day)).min() else {
+        return stats;
+    };
+
+    let mut run = 0;
+    let mut run_start = first;
+    let mut day = first;
+    while day <= today {
+        if goal_met(&day) {
+            if run == 0 {
+                run_start = day;
+            }
+            run += 1;
+            if run > stats.longest_days {
+                stats.longest_days = run;
+                stats.longest_start = Some(run_start.format("%Y-%m-%d").to_string());
+                stats.longest_end = Some(day.format("%Y-%m-%d").to_string());
+            }
+        } else if !excused(&day) && day != today {
+            run = 0;
+        }
+        match day.succ_opt() {
+            Some(next) => day = next,
+            None => break,
+        }
+    }
+    stats.current_days = run;
+
+    stats
+}
+
+/// Streaks over the whole history matching `range`'s filters; its `from`/`to` window is ignored
+/// so a streak is never cut off at the window's edge.
+fn streaks_for(
+    conn: &Connection,
+    range: &AnalyticsRange,
+    settings: &AppSettings,
+    today: NaiveDate,
+) -> AppResult<StreakStats> {
+    let history = AnalyticsRange {
+        from: None,
+        to: None,
+        project_id: range.project_id,
+        tag_id: range.tag_id,
+        profile_id: range.profile_id,
+        task_id: range.task_id,
+        include_subprojects: range.include_subprojects,
+    };
+    let sessions = fetch_sessions(conn, &history)?;
+    Ok(calculate_streaks(&sessions, settings, today))
+}
+
+#[tauri::command]
+fn timer_start(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    payload: Option<StartTimerRequest>,
+) -> AppResult<TimerState> {
+    timer_start_inner(&app, state.inner(), payload)
+}
+
+#[tauri::command]
+fn timer_pause(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    reason: Option<InterruptionReason>,
+) -> AppResult<TimerState> {
+    timer_pause_inner(&app, state.inner(), reason)
+}
+
+#[tauri::command]
+fn timer_note_distraction(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    reason: InterruptionReason,
+) -> AppResult<TimerState> {
+    timer_note_distraction_inner(&app, state.inner(), reason)
+}
+
+#[tauri::command]
+fn timer_resume(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    payload: Option<StartTimerRequest>,
+) -> AppResult<TimerState> {
+    timer_resume_inner(&app, state.inner(), payload)
+}
+
+#[tauri::command]
+fn timer_skip(app: AppHandle, state: State<'_, AppState>) -> AppResult<TimerState> {
+    timer_skip_inner(&app, state.inner())
+}
+
+#[tauri::command]
+fn timer_reset_phase(app: AppHandle, state: State<'_, AppState>) -> AppResult<TimerState> {
+    timer_reset_phase_inner(&app, state.inner())
+}
+
+#[tauri::command]
+fn timer_adjust(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    delta_seconds: i64,
+) -> AppResult<TimerState> {
+    timer_adjust_inner(&app, state.inner(), delta_seconds)
+}
+
+#[tauri::command]
+fn timer_get_state(state: State<'_, AppState>) -> AppResult<TimerState> {
+    timer_get_state_inner(state.inner())
+}
+
+/// Returns the phase recovered at startup once; later calls return `None`.
+#[tauri::command]
+fn timer_take_recovered(state: State<'_, AppState>) -> AppResult<Option<PhaseRecoveredEvent>> {
+    let mut model = lock_model(&state)?;
+    Ok(model.recovered.take())
+}
+
+#[tauri::command]
+fn timer_set_context(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    payload: StartTimerRequest,
+) -> AppResult<TimerState> {
+    let timer = {
+        let mut model = lock_model(&state)?;
+        refresh_remaining(&mut model.timer);
+
+        apply_timer_context(&mut model, payload)?;
+
+        save_timer_state(&model.conn, &model.timer)?;
+        model.timer.clone()
+    };
+
+    emit_timer_state(&app, &timer);
+    Ok(timer)
+}
+
+#[tauri::command]
+fn session_complete(
+    payload: CompleteSessionRequest,
+    state: State<'_, AppState>,
+) -> Result<SessionRecord, SessionError> {
+    let mut model = lock_model(&state)?;
+    log_session(&mut model.conn, payload, now_ts())
+}
+
+#[tauri::command]
+fn analytics_get_summary(
+    range: AnalyticsRange,
+    state: State<'_, AppState>,
+) -> AppResult<AnalyticsSummary> {
+    let model = lock_model(&state)?;
+    let sessions = fetch_sessions(&model.conn, &range)?;
+    let day_start_min = model.settings.day_start_minutes();
+
+    let mut total_focus_sec = 0;
+    let mut completed_pomodoros = 0;
+    let mut interruptions = 0;
+    let mut overtime_sec = 0;
+    let mut adjusted_sessions = 0;
+    let mut days_with_focus = HashSet::new();
+
+    for session in &sessions {
+        if session.counts_as_focus {
+            total_focus_sec += session.duration_sec;
+            interruptions += session.interruptions;
+            overtime_sec += session.overtime_sec;
+            if session.adjusted_sec != 0 {
+                adjusted_sessions += 1;
+            }
+            if session.completed {
+                completed_pomodoros += 1;
+            }
+            if session.duration_sec > 0 {
+                days_with_focus.insert(session_day(session, day_start_min));
+            }
+        }
+    }
+
+    let avg_daily_focus_sec = if days_with_focus.is_empty() {
+        0
+    } else {
+        total_focus_sec / days_with_focus.len() as i64
+    };
+    let streaks = streaks_for(
+        &model.conn,
+        &range,
+        &model.settings,
+        analytics_day(now_ts(), None, day_start_min),
+    )?;
+
+    Ok(AnalyticsSummary {
+        total_focus_sec,
+        completed_pomodoros,
+        streak_days: streaks.current_days,
+        longest_streak_days: streaks.longest_days,
+        interruptions,
+        avg_daily_focus_sec,
+        overtime_sec,
+        adjusted_sessions,
+    })
+}
+
+#[tauri::command]
+fn analytics_get_streaks(
+    range: AnalyticsRange,
+    state: State<'_, AppState>,
+) -> AppResult<StreakStats> {
+    let model = lock_model(&state)?;
+    let today = analytics_day(now_ts(), None, model.settings.day_start_minutes());
+    streaks_for(&model.conn, &range, &model.settings, today)
+}
+
+/// Focus totals per day, ISO week, month or year, keyed by the bucket's first day.
+///
+/// With `zero_fill`, every bucket from `range.from` (or the first session) through
+/// `range.to` (or now) is present, empty or not.
+fn focus_timeseries(
+    conn: &Connection,
+    range: &AnalyticsRange,
+    granularity: TimeseriesGranularity,
+    zero_fill: bool,
+    day_start_min: i64,
+) -> AppResult<Vec<TimeseriesPoint>> {
+    let sessions = fetch_sessions(conn, range)?;
+    let empty_point = |start: NaiveDate| TimeseriesPoint {
+        date: start.format("%Y-%m-%d").to_string(),
+        focus_seconds: 0,
+        completed_pomodoros: 0,
+        interruptions: 0,
+    };
+
+    let mut buckets: BTreeMap<NaiveDate, TimeseriesPoint> = BTreeMap::new();
+    for session in &sessions {
+        if !session.counts_as_focus {
+            continue;
+        }
+
+        let start = granularity.bucket_start(session_day(session, day_start_min));
+        let entry = buckets.entry(start).or_insert_with(|| empty_point(start));
+        entry.focus_seconds += session.duration_sec;
+        entry.interruptions += session.interruptions;
+        if session.completed {
+            entry.completed_pomodoros += 1;
+        }
+    }
+
+    if zero_fill {
+        let first = range
+            .from
+            .or_else(|| sessions.iter().map(|session| session.ended_at).min());
+        if let Some(first) = first {
+            let day = |timestamp| analytics_day(timestamp, None, day_start_min);
+            let last = granularity.bucket_start(day(range.to.unwrap_or_else(now_ts)));
+            let mut current = Some(granularity.bucket_start(day(first)));
+            while let Some(start) = current.filter(|start| *start <= last) {
+                buckets.entry(start).or_insert_with(|| empty_point(start));
+                current = granularity.next_bucket(start);
+            }
+        }
+    }
+
+    Ok(buckets.into_values().collect())
+}
+
+#[tauri::command]
+fn analytics_get_timeseries(
+    range: AnalyticsRange,
+    granularity: Option<TimeseriesGranularity>,
+    zero_fill: Option<bool>,
+    state: State<'_, AppState>,
+) -> AppResult<Vec<TimeseriesPoint>> {
+    let model = lock_model(&state)?;
+    focus_timeseries(
+        &model.conn,
+        &range,
+        granularity.unwrap_or_default(),
+        zero_fill.unwrap_or(false),
+        model.settings.day_start_minutes(),
+    )
+}
+
+/// Focus totals per project or per tag for the sessions in `range`.
+fn focus_breakdown(
+    conn: &Connection,
+    range: &AnalyticsRange,
+    dimension: BreakdownDimension,
+) -> AppResult<Vec<BreakdownRow>> {
+    let (sessions_query, values) = build_sessions_query(range);
+    let (grouping, fallback) = match dimension {
+        BreakdownDimension::Project => (
+            "SELECT p.id AS key_id, p.name AS label, s.*
+             FROM scoped s
+             LEFT JOIN projects p ON p.id = s.project_id AND p.deleted_at IS NULL",
+            "No project",
+        ),
+        BreakdownDimension::Tag => (
+            "SELECT t.id AS key_id, t.name AS label, s.*
+             FROM scoped s
+             LEFT JOIN session_tags st ON st.session_id = s.id
+                 AND st.tag_id IN (SELECT id FROM tags WHERE deleted_at IS NULL)
+             LEFT JOIN tags t ON t.id = st.tag_id",
+            "Untagged",
+        ),
+    };
+    let query = format!(
+        "WITH scoped AS (
+             SELECT id, project_id, duration_sec, completed, interruptions FROM sessions
+             WHERE id IN (SELECT id FROM ({sessions_query})) AND counts_as_focus = 1
+         )
+         SELECT key_id, label, SUM(duration_sec), SUM(completed), SUM(interruptions), COUNT(*),
+                (SELECT COALESCE(SUM(duration_sec), 0) FROM scoped),
+                (SELECT COALESCE(SUM(completed), 0) FROM scoped)
+         FROM ({grouping})
+         GROUP BY key_id
+         ORDER BY SUM(duration_sec) DESC, label"
+    );
+
+    let percent = |part: i64, total: i64| {
+        if total > 0 {
+            part as f64 * 100.0 / total as f64
+        } else {
+            0.0
+        }
+    };
+    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params_from_iter(values), |row| {
+            let focus_seconds: i64 = row.get(2)?;
+            let completed_pomodoros: i64 = row.get(3)?;
+            let sessions: i64 = row.get(5)?;
+            Ok(BreakdownRow {
+                id: row.get(0)?,
+                label: row
+                    .get::<_, Option<String>>(1)?
+                    .unwrap_or_else(|| fallback.to_string()),
+                focus_seconds,
+                completed_pomodoros,
+                interruptions: row.get(4)?,
+                sessions,
+                completion_rate: completed_pomodoros as f64 / sessions as f64,
+                focus_percent: percent(focus_seconds, row.get(6)?),
+                pomodoro_percent: percent(completed_pomodoros, row.get(7)?),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut breakdown = Vec::new();
+    for row in rows {
+        breakdown.push(row.map_err(|e| e.to_string())?);
+    }
+    // Subprojects can share a name, so project rows carry the full path.
+    if dimension == BreakdownDimension::Project {
+        let project_paths: HashMap<i64, String> = fetch_projects(conn)?
+            .into_iter()
+            .map(|project| (project.id, project.path))
+            .collect();
+        for row in &mut breakdown {
+            if let Some(path) = row.id.and_then(|id| project_paths.get(&id)) {
+                row.label = path.clone();
+            }
+        }
+    }
+    Ok(breakdown)
+}
+
+#[tauri::command]
+fn analytics_get_breakdown(
+    range: AnalyticsRange,
+    dimension: BreakdownDimension,
+    state: State<'_, AppState>,
+) -> AppResult<Vec<BreakdownRow>> {
+    let model = lock_model(&state)?;
+    focus_breakdown(&model.conn, &range, dimension)
+}
+
+/// Splits `[start, end)` at hour boundaries at `offset` into `(weekday, hour, seconds)` slots.
+/// The weekday is that of the analytics day, so hours before the day start count toward the
+/// day before.
+fn local_hour_slots(
+    start: i64,
+    end: i64,
+    offset: FixedOffset,
+    day_start_min: i64,
+) -> Vec<(u32, u32, i64)> {
+    let mut slots = Vec::new();
+    let mut current = start;
+    while current < end {
+        let Some(local) = offset.timestamp_opt(current, 0).single() else {
+            break;
+        };
+        let into_hour = i64::from(local.minute() * 60 + local.second());
+        let next = (current + 3600 - into_hour).min(end);
+        let day = analytics_day(current, Some(offset.local_minus_utc()), day_start_min);
+        slots.push((
+            day.weekday().num_days_from_monday(),
+            local.hour(),
+            next - current,
+        ));
+        current = next;
+    }
+    slots
+}
+
+/// Wall-clock stretches of a session that were not paused.
+fn active_intervals(session: &SessionRecord) -> Vec<(i64, i64)> {
+    let mut intervals = Vec::new();
+    let mut cursor = session.started_at;
+    for pause in &session.pauses {
+        let paused_at = pause.paused_at.clamp(cursor, session.ended_at);
+        intervals.push((cursor, paused_at));
+        cursor = pause
+            .resumed_at
+            .unwrap_or(session.ended_at)
+            .clamp(paused_at, session.ended_at);
+    }
+    intervals.push((cursor, session.ended_at));
+    intervals.retain(|(start, end)| end > start);
+    intervals
+}
+
+/// Focus time in a weekday × hour-of-day grid, with sessions split across the hours they span.
+///
+/// A session's focus seconds are spread over its unpaused time, so edits and overtime
+/// still add up to `duration_sec`. Completed pomodoros count in the hour they ended.
+/// Hours are local to where each session was recorded.
+fn focus_heatmap(
+    conn: &Connection,
+    range: &AnalyticsRange,
+    day_start_min: i64,
+) -> AppResult<Vec<HeatmapCell>> {
+    let mut cells: Vec<HeatmapCell> = (0..7)
+        .flat_map(|weekday| {
+            (0..24).map(move |hour| HeatmapCell {
+                weekday,
+                hour,
+                focus_seconds: 0,
+                completed_pomodoros: 0,
+            })
+        })
+        .collect();
+    let index = |weekday: u32, hour: u32| (weekday * 24 + hour) as usize;
+
+    for session in fetch_sessions(conn, range)? {
+        if !session.counts_as_focus {
+            continue;
+        }
+        let offset = resolve_utc_offset(session.ended_at, session.utc_offset_sec);
+        let last_slot = local_hour_slots(
+            session.ended_at - 1,
+            session.ended_at,
+            offset,
+            day_start_min,
+        );
+        if session.completed {
+            if let Some((weekday, hour, _)) = last_slot.first() {
+                cells[index(*weekday, *hour)].completed_pomodoros += 1;
+            }
+        }
+        if session.duration_sec <= 0 {
+            continue;
+        }
+
+        let slots: Vec<(u32, u32, i64)> = active_intervals(&session)
+            .into_iter()
+            .flat_map(|(start, end)| local_hour_slots(start, end, offset, day_start_min))
+            .collect();
+        let active: i64 = slots.iter().map(|(_, _, seconds)| seconds).sum();
+        if active == 0 {
+            if let Some((weekday, hour, _)) = last_slot.first() {
+                cells[index(*weekday, *hour)].focus_seconds += session.duration_sec;
+            }
+            continue;
+        }
+
+        // Scale to `duration_sec`, handing rounding leftovers to the last slot.
+        let mut assigned = 0;
+        for (position, (weekday, hour, seconds)) in slots.iter().enumerate() {
+            let share = if position + 1 == slots.len() {
+                session.duration_sec - assigned
+            } else {
+                session.duration_sec * seconds / active
+            };
+            assigned += share;
+            cells[index(*weekday, *hour)].focus_seconds += share;
+        }
+    }
+
+    Ok(cells)
+}
+
+#[tauri::command]
+fn analytics_get_heatmap(
+    range: AnalyticsRange,
+    state: State<'_, AppState>,
+) -> AppResult<Vec<HeatmapCell>> {
+    let model = lock_model(&state)?;
+    focus_heatmap(&model.conn, &range, model.settings.day_start_minutes())
+}
+
+fn interruption_breakdown(
+    conn: &Connection,
+    range: &AnalyticsRange,
+) -> AppResult<Vec<InterruptionReasonStat>> {
+    let (sessions_query, values) = build_sessions_query(range);
+    let query = format!(
+        "SELECT kind, LOWER(TRIM(note)) AS reason,
+                SUM(CASE WHEN paused = 1 THEN 1 ELSE 0 END),
+                SUM(CASE WHEN paused = 0 THEN 1 ELSE 0 END)
+         FROM interruption_log
+         WHERE session_id IN (SELECT id FROM ({sessions_query}))
+         GROUP BY kind, reason
+         ORDER BY COUNT(*) DESC, kind, reason"
+    );
+    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params_from_iter(values), |row| {
+            Ok(InterruptionReasonStat {
+                kind: interruption_kind_from_db(0, row.get(0)?)?,
+                note: row.get(1)?,
+                pauses: row.get(2)?,
+                distractions: row.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut stats = Vec::new();
+    for row in rows {
+        stats.push(row.map_err(|e| e.to_string())?);
+    }
+    Ok(stats)
+}
+
+/// Sets or clears the ratings of a focus session.
+fn rate_session(
+    conn: &Connection,
+    session_id: i64,
+    focus_rating: Option<i64>,
+    energy_level: Option<i64>,
+) -> AppResult<SessionRecord> {
+    for (field, value) in [
+        ("focus rating", focus_rating),
+        ("energy level", energy_level),
+    ] {
+        if value.is_some_and(|value| !(1..=5).contains(&value)) {
+            return Err(format!("{field} must be between 1 and 5"));
+        }
+    }
+    let session = fetch_session(conn, session_id)?
+        .ok_or_else(|| format!("session {session_id} not found"))?;
+    if !session.counts_as_focus {
+        return Err("only focus sessions can be rated".to_string());
+    }
+
+    conn.execute(
+        "UPDATE sessions SET focus_rating = ?1, energy_level = ?2 WHERE id = ?3",
+        params![focus_rating, energy_level, session_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(SessionRecord {
+        focus_rating,
+        energy_level,
+        ..session
+    })
+}
+
+fn rating_buckets<K: Ord>(
+    sessions: &[&SessionRecord],
+    bucket: impl Fn(&SessionRecord) -> (K, String, String),
+) -> Vec<RatingBucket> {
+    let mut groups: BTreeMap<K, (String, String, Vec<&SessionRecord>)> = BTreeMap::new();
+    for session in sessions {
+        let (order, key, label) = bucket(session);
+        groups
+            .entry(order)
+            .or_insert_with(|| (key, label, Vec::new()))
+            .2
+            .push(session);
+    }
+
+    groups
+        .into_values()
+        .map(|(key, label, sessions)| {
+            let ratings: Vec<f64> = sessions
+                .iter()
+                .filter_map(|session| session.focus_rating)
+                .map(|rating| rating as f64)
+                .collect();
+            let energy: Vec<f64> = sessions
+                .iter()
+                .filter_map(|session| session.energy_level)
+                .map(|level| level as f64)
+                .collect();
+            RatingBucket {
+                key,
+                label,
+                sessions: sessions.len() as i64,
+                avg_focus_rating: ratings.iter().sum::<f64>() / ratings.len() as f64,
+                avg_energy_level: (!energy.is_empty())
+                    .then(|| energy.iter().sum::<f64>() / energy.len() as f64),
+            }
+        })
+        .collect()
+}
+
+/// Pearson correlation coefficient; `None` with fewer than two points or no variance.
+fn pearson(points: &[(f64, f64)]) -> Option<f64> {
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in points {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+    (var_x > 0.0 && var_y > 0.0).then(|| cov / (var_x.sqrt() * var_y.sqrt()))
+}
+
+fn rating_correlations(conn: &Connection, range: &AnalyticsRange) -> AppResult<RatingCorrelations> {
+    let sessions = fetch_sessions(conn, range)?;
+    let rated: Vec<&SessionRecord> = sessions
+        .iter()
+        .filter(|session| session.counts_as_focus && session.focus_rating.is_some())
+        .collect();
+    let project_paths: HashMap<i64, String> = fetch_projects(conn)?
+        .into_iter()
+        .map(|project| (project.id, project.path))
+        .collect();
+
+    let by_hour = rating_buckets(&rated, |session| {
+        let hour = resolve_utc_offset(session.ended_at, session.utc_offset_sec)
+            .timestamp_opt(session.started_at, 0)
+            .single()
+            .map(|time| time.hour())
+            .unwrap_or(0);
+        (hour, hour.to_string(), format!("{hour:02}:00"))
+    });
+    let mut by_project = rating_buckets(&rated, |session| match session.project_id {
+        Some(id) => (
+            Some(id),
+            id.to_string(),
+            project_paths
+                .get(&id)
+                .cloned()
+                .unwrap_or_else(|| format!("Project {id}")),
+        ),
+        None => (None, "none".to_string(), "No project".to_string()),
+    });
+    by_project.sort_by_key(|bucket| std::cmp::Reverse(bucket.sessions));
+    let by_interruptions = rating_buckets(&rated, |session| {
+        let count = session.interruptions.min(3);
+        let key = if count == 3 {
+            "3+".to_string()
+        } else {
+            count.to_string()
+        };
+        (count, key.clone(), key)
+    });
+    let by_length = rating_buckets(&rated, |session| {
+        const EDGES: [i64; 4] = [15, 30, 45, 60];
+        let minutes = session.duration_sec / 60;
+        let index = EDGES.iter().take_while(|edge| minutes >= **edge).count();
+        let (key, label) = match index {
+            0 => ("0-15".to_string(), "Under 15 min".to_string()),
+            4 => ("60+".to_string(), "60 min or more".to_string()),
+            _ => (
+                format!("{}-{}", EDGES[index - 1], EDGES[index]),
+                format!("{}–{} min", EDGES[index - 1], EDGES[index]),
+            ),
+        };
+        (index, key, label)
+    });
+
+    let points = |x: fn(&SessionRecord) -> f64| -> Vec<(f64, f64)> {
+        rated
+            .iter()
+            .filter_map(|session| Some((x(session), session.focus_rating? as f64)))
+            .collect()
+    };
+    Ok(RatingCorrelations {
+        rated_sessions: rated.len() as i64,
+        by_hour,
+        by_project,
+        by_interruptions,
+        by_length,
+        interruptions_correlation: pearson(&points(|session| session.interruptions as f64)),
+        length_correlation: pearson(&points(|session| session.duration_sec as f64)),
+    })
+}
+
+#[tauri::command]
+fn analytics_get_ratings(
+    range: AnalyticsRange,
+    state: State<'_, AppState>,
+) -> AppResult<RatingCorrelations> {
+    let model = lock_model(&state)?;
+    rating_correlations(&model.conn, &range)
+}
+
+#[tauri::command]
+fn analytics_get_tag_namespace(
+    range: AnalyticsRange,
+    tag_id: i64,
+    state: State<'_, AppState>,
+) -> AppResult<Vec<TagNamespaceStat>> {
+    let model = lock_model(&state)?;
+    tag_namespace_breakdown(&model.conn, &range, tag_id)
+}
+
+#[tauri::command]
+fn analytics_get_interruptions(
+    range: AnalyticsRange,
+    state: State<'_, AppState>,
+) -> AppResult<Vec<InterruptionReasonStat>> {
+    let model = lock_model(&state)?;
+    interruption_breakdown(&model.conn, &range)
+}
+
+#[tauri::command]
+fn projects_list(state: State<'_, AppState>) -> AppResult<Vec<Project>> {
+    let model = lock_model(&state)?;
+    fetch_projects(&model.conn)
+}
+
+fn upsert_project(conn: &Connection, input: ProjectInput) -> AppResult<Project> {
+    let existing = match input.id {
+        Some(id) => {
+            Some(fetch_project(conn, id)?.ok_or_else(|| format!("project {id} not found"))?)
+        }
+        None => None,
+    };
+    ensure_name_not_in_trash(conn, TrashKind::Project, &input.name)?;
+    let parent_id = match input.parent_id {
+        Some(parent_id) => parent_id,
+        None => existing.as_ref().and_then(|project| project.parent_id),
+    };
+    if let Some(parent_id) = parent_id {
+        if !row_exists(conn, "projects", parent_id)? {
+            return Err(format!("project {parent_id} not found"));
+        }
+        if let Some(id) = input.id {
+            if project_subtree_ids(conn, id)?.contains(&parent_id) {
+                return Err(
+                    "a project cannot be nested under itself or its subprojects".to_string()
+                );
+            }
+        }
+    }
+
+    let archived = input.archived.unwrap_or(false);
+    let id = if let Some(id) = input.id {
+        conn.execute(
+            "UPDATE projects SET name = ?1, color = ?2, archived = ?3, parent_id = ?4 WHERE id = ?5",
+            params![input.name, input.color, archived as i64, parent_id, id],
+        )
+        .map_err(|e| e.to_string())?;
+        id
+    } else {
+        conn.execute(
+            "INSERT INTO projects (name, color, archived, parent_id, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![input.name, input.color, archived as i64, parent_id, now_ts()],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.last_insert_rowid()
+    };
+
+    fetch_project(conn, id)?.ok_or_else(|| format!("project {id} not found"))
+}
+
+#[tauri::command]
+fn projects_upsert(input: ProjectInput, state: State<'_, AppState>) -> AppResult<Project> {
+    let model = lock_model(&state)?;
+    upsert_project(&model.conn, input)
+}
+
+#[tauri::command]
+fn tags_list(state: State<'_, AppState>) -> AppResult<Vec<Tag>> {
+    let model = lock_model(&state)?;
+    fetch_tags(&model.conn)
+}
+
+#[tauri::command]
+fn tags_upsert(input: TagInput, state: State<'_, AppState>) -> AppResult<Tag> {
+    let mut model = lock_model(&state)?;
+    upsert_tag(&mut model.conn, input)
+}
+
+fn projects_remove_inner(
+    app: &AppHandle,
+    state: &AppState,
+    project_id: i64,
+    reassign_to: Option<i64>,
+) -> AppResult<()> {
+    let timer = {
+        let mut model = state.model.lock().map_err(|e| e.to_string())?;
+        remove_project(&mut model.conn, project_id, reassign_to)?;
+        if retarget_timer_context(&mut model.timer, Some((project_id, reassign_to)), None) {
+            save_timer_state(&model.conn, &model.timer)?;
+            Some(model.timer.clone())
+        } else {
+            None
+        }
+    };
+
+    if let Some(timer) = timer {
+        emit_timer_state(app, &timer);
+    }
+    Ok(())
+}
+
+fn tags_remove_inner(
+    app: &AppHandle,
+    state: &AppState,
+    tag_id: i64,
+    reassign_to: Option<i64>,
+) -> AppResult<()> {
+    let timer = {
+        let mut model = state.model.lock().map_err(|e| e.to_string())?;
+        remove_tag(&mut model.conn, tag_id, reassign_to)?;
+        if retarget_timer_context(&mut model.timer, None, Some((tag_id, reassign_to))) {
+            save_timer_state(&model.conn, &model.timer)?;
+            Some(model.timer.clone())
+        } else {
+            None
+        }
+    };
+
+    if let Some(timer) = timer {
+        emit_timer_state(app, &timer);
+    }
+    Ok(())
+}
+
+/// Deletes a project; its sessions move to `reassign_to` or lose their project.
+#[tauri::command]
+fn projects_delete(
+    app: AppHandle,
+    project_id: i64,
+    reassign_to: Option<i64>,
+    state: State<'_, AppState>,
+) -> AppResult<()> {
+    projects_remove_inner(&app, state.inner(), project_id, reassign_to)
+}
+
+/// Moves everything recorded under `source_id` to `target_id` and deletes the source.
+#[tauri::command]
+fn projects_merge(
+    app: AppHandle,
+    source_id: i64,
+    target_id: i64,
+    state: State<'_, AppState>,
+) -> AppResult<()> {
+    projects_remove_inner(&app, state.inner(), source_id, Some(target_id))
+}
+
+/// Deletes a tag; its sessions are re-tagged with `reassign_to` or untagged.
+#[tauri::command]
+fn tags_delete(
+    app: AppHandle,
+    tag_id: i64,
+    reassign_to: Option<i64>,
+    state: State<'_, AppState>,
+) -> AppResult<()> {
+    tags_remove_inner(&app, state.inner(), tag_id, reassign_to)
+}
+
+#[tauri::command]
+fn tags_merge(
+    app: AppHandle,
+    source_id: i64,
+    target_id: i64,
+    state: State<'_, AppState>,
+) -> AppResult<()> {
+    tags_remove_inner(&app, state.inner(), source_id, Some(target_id))
+}
+
+#[tauri::command]
+fn tasks_list(
+    project_id: Option<i64>,
+    include_done: Option<bool>,
+    state: State<'_, AppState>,
+) -> AppResult<Vec<Task>> {
+    let model = lock_model(&state)?;
+    fetch_tasks(&model.conn, project_id, include_done.unwrap_or(false))
+}
+
+#[tauri::command]
+fn tasks_upsert(input: TaskInput, state: State<'_, AppState>) -> AppResult<Task> {
+    let model = lock_model(&state)?;
+    upsert_task(&model.conn, input)
+}
+
+/// Stores the given order; tasks missing from `task_ids` keep their position.
+#[tauri::command]
+fn tasks_reorder(task_ids: Vec<i64>, state: State<'_, AppState>) -> AppResult<()> {
+    let mut model = lock_model(&state)?;
+    let tx = model.conn.transaction().map_err(|e| e.to_string())?;
+    for (position, task_id) in task_ids.iter().enumerate() {
+        tx.execute(
+            "UPDATE tasks SET position = ?1 WHERE id = ?2",
+            params![position as i64, task_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn tasks_delete(app: AppHandle, task_id: i64, state: State<'_, AppState>) -> AppResult<()> {
+    let timer = {
+        let mut model = lock_model(&state)?;
+        model
+            .conn
+            .execute("DELETE FROM tasks WHERE id = ?1", params![task_id])
+            .map_err(|e| e.to_string())?;
+
+        if model.timer.current_task_id == Some(task_id) {
+            model.timer.current_task_id = None;
+            save_timer_state(&model.conn, &model.timer)?;
+            Some(model.timer.clone())
+        } else {
+            None
+        }
+    };
+
+    if let Some(timer) = timer {
+        emit_timer_state(&app, &timer);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn day_plan_get(date: Option<String>, state: State<'_, AppState>) -> AppResult<Vec<DayPlanItem>> {
+    let model = lock_model(&state)?;
+    let date = date.unwrap_or_else(|| day_key(now_ts(), model.settings.day_start_minutes()));
+    validate_day_key(&date)?;
+    fetch_day_plan(&model.conn, &date)
+}
+
+/// Saves a day's queue; an idle timer picks up today's next item straight away.
+#[tauri::command]
+fn day_plan_set(
+    app: AppHandle,
+    date: Option<String>,
+    items: Vec<DayPlanItemInput>,
+    state: State<'_, AppState>,
+) -> AppResult<Vec<DayPlanItem>> {
+    let (items, timer) = {
+        let mut model = lock_model(&state)?;
+        let day_start_min = model.settings.day_start_minutes();
+        let now = now_ts();
+        let today = day_key(now, day_start_min);
+        let date = date.unwrap_or_else(|| today.clone());
+        let items = set_day_plan(&mut model.conn, &date, items)?;
+
+        let model = &mut *model;
+        let timer = if date == today && model.timer.started_at.is_none() {
+            select_day_plan_item(&model.conn, &mut model.timer, now, day_start_min)?;
+            save_timer_state(&model.conn, &model.timer)?;
+            Some(model.timer.clone())
+        } else {
+            None
+        };
+        (items, timer)
+    };
+
+    if let Some(timer) = timer {
+        emit_timer_state(&app, &timer);
+    }
+    Ok(items)
+}
+
+#[tauri::command]
+fn day_plan_report(date: Option<String>, state: State<'_, AppState>) -> AppResult<DayPlanReport> {
+    let model = lock_model(&state)?;
+    let day_start_min = model.settings.day_start_minutes();
+    let date = date.unwrap_or_else(|| day_key(now_ts(), day_start_min));
+    day_plan_report_for(&model.conn, &date, day_start_min)
+}
+
+#[tauri::command]
+fn cycle_plans_list(state: State<'_, AppState>) -> AppResult<Vec<CyclePlan>> {
+    let model = lock_model(&state)?;
+    fetch_cycle_plans(&model.conn)
+}
+
+#[tauri::command]
+fn cycle_plans_upsert(
+    app: AppHandle,
+    input: CyclePlanInput,
+    state: State<'_, AppState>,
+) -> AppResult<CyclePlan> {
+    let name = input.name.trim().to_string();
+    if name.is_empty() {
+        return Err("cycle plan name cannot be empty".to_string());
+    }
+    let steps = normalize_plan_steps(input.steps)?;
+
+    let (plan, timer) = {
+        let mut model = lock_model(&state)?;
+
+        let id = {
+            let tx = model.conn.transaction().map_err(|e| e.to_string())?;
+            let id = if let Some(id) = input.id {
+                let updated = tx
+                    .execute(
+                        "UPDATE cycle_plans SET name = ?1 WHERE id = ?2",
+                        params![name, id],
+                    )
+                    .map_err(|e| e.to_string())?;
+                if updated == 0 {
+                    return Err(format!("cycle plan {id} not found"));
+                }
+                tx.execute(
+                    "DELETE FROM cycle_plan_steps WHERE plan_id = ?1",
+                    params![id],
+                )
+                .map_err(|e| e.to_string())?;
+                id
+            } else {
+                tx.execute(
+                    "INSERT INTO cycle_plans (name, created_at) VALUES (?1, ?2)",
+                    params![name, now_ts()],
+                )
+                .map_err(|e| e.to_string())?;
+                tx.last_insert_rowid()
+            };
+
+            for (position, step) in steps.iter().enumerate() {
+                tx.execute(
+                    "INSERT INTO cycle_plan_steps (plan_id, position, phase, label, duration_min, counts_as_focus)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![
+                        id,
+                        position as i64,
+                        step.phase.as_db_value(),
+                        step.label,
+                        step.duration_min,
+                        step.counts_as_focus as i64,
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+            tx.commit().map_err(|e| e.to_string())?;
+            id
+        };
+
+        let plan = fetch_cycle_plan(&model.conn, id)?
+            .ok_or_else(|| format!("cycle plan {id} not found"))?;
+
+        // Editing the active plan takes effect immediately for an idle timer,
+        // otherwise from the next phase on.
+        let timer = if model.timer.plan_id == Some(id) {
+            model.plan = Some(plan.clone());
+            if model.timer.started_at.is_none() {
+                model.timer.remaining_seconds = 0;
+            }
+            let settings = model.timer_settings();
+            model.timer =
+                normalize_timer_state(model.timer.clone(), &settings, model.plan.as_ref());
+            save_timer_state(&model.conn, &model.timer)?;
+            Some(model.timer.clone())
+        } else {
+            None
+        };
+
+        (plan, timer)
+    };
+
+    if let Some(timer) = timer {
+        emit_timer_state(&app, &timer);
+    }
+    Ok(plan)
+}
+
+#[tauri::command]
+fn cycle_plans_delete(app: AppHandle, plan_id: i64, state: State<'_, AppState>) -> AppResult<()> {
+    let timer = {
+        let mut model = lock_model(&state)?;
+        model
+            .conn
+            .execute("DELETE FROM cycle_plans WHERE id = ?1", params![plan_id])
+            .map_err(|e| e.to_string())?;
+
+        if model.timer.plan_id == Some(plan_id) {
+            model.plan = None;
+            let settings = model.timer_settings();
+            model.timer = normalize_timer_state(model.timer.clone(), &settings, None);
+            save_timer_state(&model.conn, &model.timer)?;
+            Some(model.timer.clone())
+        } else {
+            None
+        }
+    };
+
+    if let Some(timer) = timer {
+        emit_timer_state(&app, &timer);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn timer_set_plan(
+    app: AppHandle,
+    plan_id: Option<i64>,
+    state: State<'_, AppState>,
+) -> AppResult<TimerState> {
+    let timer = {
+        let mut model = lock_model(&state)?;
+        if model.timer.started_at.is_some() {
+            return Err(
+                "finish or skip the current phase before switching cycle plans".to_string(),
+            );
+        }
+
+        let plan = match plan_id {
+            Some(id) => Some(
+                fetch_cycle_plan(&model.conn, id)?
+                    .ok_or_else(|| format!("cycle plan {id} not found"))?,
+            ),
+            None => None,
+        };
+
+        model.timer.plan_id = plan_id;
+        model.timer.plan_step = 0;
+        model.timer.phase = TimerPhase::Focus;
+        model.timer.adjusted_seconds = 0;
+        let step = current_step(&model.timer, &model.timer_settings(), plan.as_ref());
+        apply_step(&mut model.timer, &step);
+        model.timer.remaining_seconds = model.timer.phase_total_seconds;
+        model.timer.is_running = false;
+        model.timer.target_ends_at = None;
+        model.timer.interruptions = 0;
+        model.plan = plan;
+
+        save_timer_state(&model.conn, &model.timer)?;
+        model.timer.clone()
+    };
+
+    emit_timer_state(&app, &timer);
+    Ok(timer)
+}
+
+#[tauri::command]
+fn timer_profiles_list(state: State<'_, AppState>) -> AppResult<Vec<TimerProfile>> {
+    let model = lock_model(&state)?;
+    fetch_timer_profiles(&model.conn)
+}
+
+#[tauri::command]
+fn timer_profiles_upsert(
+    app: AppHandle,
+    input: TimerProfileInput,
+    state: State<'_, AppState>,
+) -> AppResult<TimerProfile> {
+    let input = normalize_profile(input)?;
+
+    let (profile, timer) = {
+        let mut model = lock_model(&state)?;
+
+        let id = if let Some(id) = input.id {
+            let updated = model
+                .conn
+                .execute(
+                    "UPDATE timer_profiles
+                     SET name = ?1, focus_min = ?2, short_break_min = ?3, long_break_min = ?4, long_break_every = ?5
+                     WHERE id = ?6",
+                    params![
+                        input.name,
+                        input.focus_min,
+                        input.short_break_min,
+                        input.long_break_min,
+                        input.long_break_every,
+                        id
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+            if updated == 0 {
+                return Err(format!("timer profile {id} not found"));
+            }
+            id
+        } else {
+            model
+                .conn
+                .execute(
+                    "INSERT INTO timer_profiles (name, focus_min, short_break_min, long_break_min, long_break_every, created_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![
+                        input.name,
+                        input.focus_min,
+                        input.short_break_min,
+                        input.long_break_min,
+                        input.long_break_every,
+                        now_ts()
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+            model.conn.last_insert_rowid()
+        };
+
+        let profile = fetch_timer_profile(&model.conn, id)?
+            .ok_or_else(|| format!("timer profile {id} not found"))?;
+
+        let timer = if model.timer.profile_id == Some(id) {
+            model.profile = Some(profile.clone());
+            sync_idle_timer(&mut model);
+            save_timer_state(&model.conn, &model.timer)?;
+            Some(model.timer.clone())
+        } else {
+            None
+        };
+
+        (profile, timer)
+    };
+
+    if let Some(timer) = timer {
+        emit_timer_state(&app, &timer);
+    }
+    Ok(profile)
+}
+
+#[tauri::command]
+fn timer_profiles_delete(
+    app: AppHandle,
+    profile_id: i64,
+    state: State<'_, AppState>,
+) -> AppResult<()> {
+    let timer = {
+        let mut model = lock_model(&state)?;
+        model
+            .conn
+            .execute(
+                "DELETE FROM timer_profiles WHERE id = ?1",
+                params![profile_id],
+            )
+            .map_err(|e| e.to_string())?;
+
+        if model.timer.profile_id == Some(profile_id) {
+            model.profile = None;
+            model.timer.profile_id = None;
+            sync_idle_timer(&mut model);
+            save_timer_state(&model.conn, &model.timer)?;
+            Some(model.timer.clone())
+        } else {
+            None
+        }
+    };
+
+    if let Some(timer) = timer {
+        emit_timer_state(&app, &timer);
+    }
+    Ok(())
+}
+
+/// Switches the active timer profile. A phase already in progress keeps its
+/// duration; the new profile applies from the next phase on.
+#[tauri::command]
+fn timer_set_profile(
+    app: AppHandle,
+    profile_id: Option<i64>,
+    state: State<'_, AppState>,
+) -> AppResult<TimerState> {
+    let timer = {
+        let mut model = lock_model(&state)?;
+        let profile = match profile_id {
+            Some(id) => Some(
+                fetch_timer_profile(&model.conn, id)?
+                    .ok_or_else(|| format!("timer profile {id} not found"))?,
+            ),
+            None => None,
+        };
+
+        model.timer.profile_id = profile_id;
+        model.profile = profile;
+        sync_idle_timer(&mut model);
+
+        save_timer_state(&model.conn, &model.timer)?;
+        model.timer.clone()
+    };
+
+    emit_timer_state(&app, &timer);
+    Ok(timer)
+}
+
+/// Quotes a free-text CSV field when it contains a delimiter, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[tauri::command]
+fn export_csv(range: ExportRange, state: State<'_, AppState>) -> AppResult<ExportResult> {
+    let model = lock_model(&state)?;
+    let sessions = fetch_sessions(
+        &model.conn,
+        &AnalyticsRange {
+            from: range.from,
+            to: range.to,
+            ..Default::default()
+        },
+    )?;
+    let project_paths: HashMap<i64, String> = fetch_projects(&model.conn)?
+        .into_iter()
+        .map(|project| (project.id, project.path))
+        .collect();
+
+    let mut csv = String::from(
+        "id,startedAt,endedAt,phase,durationSec,completed,interruptions,projectId,tagIds,countsAsFocus,profileId,plannedDurationSec,overtimeSec,adjustedSec,note,taskId,projectPath\n",
+    );
+
+    for s in sessions {
+        let tag_ids = s
+            .tag_ids
+            .iter()
+            .map(std::string::ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(";");
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+            s.id,
+            s.started_at,
+            s.ended_at,
+            s.phase.as_db_value(),
+            s.duration_sec,
+            s.completed,
+            s.interruptions,
+            s.project_id
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "".to_string()),
+            tag_ids,
+            s.counts_as_focus,
+            s.profile_id
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "".to_string()),
+            s.planned_duration_sec,
+            s.overtime_sec,
+            s.adjusted_sec,
+            csv_field(s.note.as_deref().unwrap_or("")),
+            s.task_id
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "".to_string()),
+            csv_field(
+                s.project_id
+                    .and_then(|id| project_paths.get(&id))
+                    .map_or("", String::as_str)
+            ),
+        ));
+    }
+
+    Ok(ExportResult {
+        filename: format!("pomodoro-sessions-{}.csv", now_ts()),
+        content: csv,
+    })
+}
+
+#[tauri::command]
+fn export_json(range: ExportRange, state: State<'_, AppState>) -> AppResult<ExportResult> {
+    let model = lock_model(&state)?;
+
+    let sessions = fetch_sessions(
+        &model.conn,
+        &AnalyticsRange {
+            from: range.from,
+            to: range.to,
+            ..Default::default()
+        },
+    )?;
+    let projects = fetch_projects(&model.conn)?;
+    let tags = fetch_tags(&model.conn)?;
+
+    let cycle_plans = fetch_cycle_plans(&model.conn)?;
+    let timer_profiles = fetch_timer_profiles(&model.conn)?;
+    let tasks = fetch_tasks(&model.conn, None, true)?;
+
+    let payload = serde_json::json!({
+      "exportedAt": now_ts(),
+      "settings": model.settings,
+      "projects": projects,
+      "tags": tags,
+      "cyclePlans": cycle_plans,
+      "timerProfiles": timer_profiles,
+      "tasks": tasks,
+      "sessions": sessions
+    });
+
+    Ok(ExportResult {
+        filename: format!("pomodoro-backup-{}.json", now_ts()),
+        content: serde_json::to_string_pretty(&payload).map_err(|e| e.to_string())?,
+    })
+}
+
+#[tauri::command]
+fn settings_get(state: State<'_, AppState>) -> AppResult<AppSettings> {
+    let model = lock_model(&state)?;
+    Ok(model.settings.clone())
+}
+
+#[tauri::command]
+fn settings_update(
+    app: AppHandle,
+    patch: AppSettingsPatch,
+    state: State<'_, AppState>,
+) -> AppResult<AppSettings> {
+    let (settings, timer) = {
+        let mut model = lock_model(&state)?;
+
+        if let Some(v) = patch.focus_min {
+            model.settings.focus_min = v;
+        }
+        if let Some(v) = patch.short_break_min {
+            model.settings.short_break_min = v;
+        }
+        if let Some(v) = patch.long_break_min {
+            model.settings.long_break_min = v;
+        }
+        if let Some(v) = patch.long_break_every {
+            model.settings.long_break_every = v;
+        }
+        if let Some(v) = patch.theme {
+            model.settings.theme = v.trim().to_lowercase();
+        }
+        if let Some(v) = patch.sound_enabled {
+            model.settings.sound_enabled = v;
+        }
+        if let Some(v) = patch.notifications_enabled {
+            model.settings.notifications_enabled = v;
+        }
+        if let Some(v) = patch.remote_control_enabled {
+            model.settings.remote_control_enabled = v;
+        }
+        if let Some(v) = patch.remote_control_port {
+            model.settings.remote_control_port = v;
+        }
+        if let Some(v) = patch.remote_control_token {
+            model.settings.remote_control_token = v;
+        }
+        if let Some(v) = patch.auto_start_breaks {
+            model.settings.auto_start_breaks = v;
+        }
+        if let Some(v) = patch.auto_start_focus {
+            model.settings.auto_start_focus = v;
+        }
+        if let Some(v) = patch.auto_start_delay_sec {
+            model.settings.auto_start_delay_sec = v;
+        }
+        if let Some(v) = patch.auto_stop_after_cycles {
+            model.settings.auto_stop_after_cycles = v;
+        }
+        if let Some(v) = patch.flow_mode_enabled {
+            model.settings.flow_mode_enabled = v;
+        }
+        if let Some(v) = patch.trash_retention_days {
+            model.settings.trash_retention_days = v;
+        }
+        if let Some(v) = patch.day_starts_at {
+            model.settings.day_starts_at = v;
+        }
+        if let Some(v) = patch.daily_goal_pomodoros {
+            model.settings.daily_goal_pomodoros = v;
+        }
+        if let Some(v) = patch.rest_weekdays {
+            model.settings.rest_weekdays = v;
+        }
+        if let Some(v) = patch.vacation_dates {
+            model.settings.vacation_dates = v;
+        }
+
+        model.settings = normalize_settings(model.settings.clone());
+        if model.settings.remote_control_token.trim().is_empty() {
+            ensure_remote_token(&mut model.settings);
+        }
+        save_json_setting(&model.conn, APP_SETTINGS_KEY, &model.settings)?;
+
+        // Keep the current phase duration in sync if timer is idle.
+        if !model.timer.is_running {
+            discard_phase_progress(&mut model.timer);
+            let step = current_step(&model.timer, &model.timer_settings(), model.plan.as_ref());
+            apply_step(&mut model.timer, &step);
+            model.timer.remaining_seconds = model.timer.phase_total_seconds;
+            model.timer.target_ends_at = None;
+            save_timer_state(&model.conn, &model.timer)?;
+        }
+
+        (model.settings.clone(), model.timer.clone())
+    };
+
+    // Start/stop/restart remote control server based on settings.
+    remote_apply(&app, &settings)?;
+
+    emit_timer_state(&app, &timer);
+    Ok(settings)
+}
+
+#[tauri::command]
+fn reset_all_data(app: AppHandle, state: State<'_, AppState>) -> AppResult<ResetAllResult> {
+    let (settings, timer) = {
+        let mut model = lock_model(&state)?;
+
+        let snapshot = reset_snapshot_path(&model.conn)?;
+        if snapshot.exists() {
+            fs::remove_file(&snapshot).map_err(|e| e.to_string())?;
+        }
+        backup_database(&model.conn, &snapshot)?;
+
+        {
+            let tx = model.conn.transaction().map_err(|e| e.to_string())?;
+            tx.execute("DELETE FROM session_tags", [])
+                .map_err(|e| e.to_string())?;
+            tx.execute("DELETE FROM session_pauses", [])
+                .map_err(|e| e.to_string())?;
+            tx.execute("DELETE FROM interruption_log", [])
+                .map_err(|e| e.to_string())?;
+            tx.execute("DELETE FROM sessions", [])
+                .map_err(|e| e.to_string())?;
+            tx.execute("DELETE FROM day_plan_items", [])
+                .map_err(|e| e.to_string())?;
+            tx.execute("DELETE FROM tasks", [])
+                .map_err(|e| e.to_string())?;
+            tx.execute("DELETE FROM projects", [])
+                .map_err(|e| e.to_string())?;
+            tx.execute("DELETE FROM tags", [])
+                .map_err(|e| e.to_string())?;
+            tx.execute("DELETE FROM cycle_plans", [])
+                .map_err(|e| e.to_string())?;
+            tx.execute("DELETE FROM timer_profiles", [])
+                .map_err(|e| e.to_string())?;
+            tx.execute("DELETE FROM settings", [])
+                .map_err(|e| e.to_string())?;
+            tx.execute(
+                "DELETE FROM sqlite_sequence WHERE name IN ('projects', 'tags', 'sessions', 'tasks', 'day_plan_items', 'cycle_plans', 'timer_profiles')",
+                [],
+            )
+            .map_err(|e| e.to_string())?;
+            tx.commit().map_err(|e| e.to_string())?;
+        }
+
+        model.settings = normalize_settings(AppSettings::default());
+        ensure_remote_token(&mut model.settings);
+        model.timer = TimerState::default_with_settings(&model.settings);
+        model.plan = None;
+        model.profile = None;
+        save_json_setting(&model.conn, APP_SETTINGS_KEY, &model.settings)?;
+        save_timer_state(&model.conn, &model.timer)?;
+        model.reset_snapshot = Some(ResetSnapshot {
+            path: snapshot,
+            settings: serde_json::to_value(&model.settings).map_err(|e| e.to_string())?,
+        });
+
+        (model.settings.clone(), model.timer.clone())
+    };
+
+    remote_apply(&app, &settings)?;
+    emit_timer_state(&app, &timer);
+    Ok(ResetAllResult { settings, timer })
+}
+
+/// Puts back everything the last `reset_all_data` of this run removed, as long as nothing
+/// has been added since.
+#[tauri::command]
+fn reset_undo(app: AppHandle, state: State<'_, AppState>) -> AppResult<ResetAllResult> {
+    let (settings, timer) = {
+        let mut model = lock_model(&state)?;
+        let snapshot = model
+            .reset_snapshot
+            .take()
+            .ok_or_else(|| "there is no reset to undo".to_string())?;
+        let restored = ensure_nothing_since_reset(&model.conn, &snapshot, &model.settings)
+            .and_then(|()| restore_snapshot(&model.conn, &snapshot.path));
+        if let Err(err) = restored {
+            model.reset_snapshot = Some(snapshot);
+            return Err(err);
+        }
+        let _ = fs::remove_file(&snapshot.path);
+
+        model.settings = load_or_create_settings(&model.conn)?;
+        let (timer, plan, profile) = load_or_create_timer(&model.conn, &model.settings)?;
+        model.timer = timer;
+        model.plan = plan;
+        model.profile = profile;
+
+        (model.settings.clone(), model.timer.clone())
+    };
+
+    remote_apply(&app, &settings)?;
+    emit_timer_state(&app, &timer);
+    Ok(ResetAllResult { settings, timer })
+}
+
+#[tauri::command]
+fn trash_list(state: State<'_, AppState>) -> AppResult<Vec<TrashItem>> {
+    let mut model = lock_model(&state)?;
+    let model = &mut *model;
+    purge_expired_trash(&mut model.conn, &model.settings, now_ts())?;
+    fetch_trash(&model.conn)
+}
+
+#[tauri::command]
+fn trash_restore(kind: TrashKind, id: i64, state: State<'_, AppState>) -> AppResult<()> {
+    let mut model = lock_model(&state)?;
+    restore_trashed(&mut model.conn, kind, id)
+}
+
+/// Purges one trashed item when `kind` and `id` are given, otherwise empties the trash.
+#[tauri::command]
+fn trash_purge(
+    kind: Option<TrashKind>,
+    id: Option<i64>,
+    state: State<'_, AppState>,
+) -> AppResult<usize> {
+    let item = match (kind, id) {
+        (Some(kind), Some(id)) => Some((kind, id)),
+        (None, None) => None,
+        _ => return Err("kind and id must be given together".to_string()),
+    };
+    let mut model = lock_model(&state)?;
+    purge_trash(&mut model.conn, item, None)
+}
+
+#[tauri::command]
+fn session_history(
+    range: AnalyticsRange,
+    state: State<'_, AppState>,
+) -> AppResult<Vec<SessionRecord>> {
+    let model = lock_model(&state)?;
+    fetch_sessions(&model.conn, &range)
+}
+
+#[tauri::command]
+fn session_search(
+    query: String,
+    range: AnalyticsRange,
+    state: State<'_, AppState>,
+) -> AppResult<Vec<SessionRecord>> {
+    let model = lock_model(&state)?;
+    search_sessions(&model.conn, &query, &range)
+}
+
+#[tauri::command]
+fn session_update(
+    payload: UpdateSessionRequest,
+    state: State<'_, AppState>,
+) -> Result<SessionRecord, SessionError> {
+    let mut model = lock_model(&state)?;
+    update_session(&mut model.conn, payload)
+}
+
+#[tauri::command]
+fn session_rate(
+    session_id: i64,
+    focus_rating: Option<i64>,
+    energy_level: Option<i64>,
+    state: State<'_, AppState>,
+) -> AppResult<SessionRecord> {
+    let model = lock_model(&state)?;
+    rate_session(&model.conn, session_id, focus_rating, energy_level)
+}
+
+#[tauri::command]
+fn session_delete(session_ids: Vec<i64>, state: State<'_, AppState>) -> AppResult<usize> {
+    let mut model = lock_model(&state)?;
+    delete_sessions(&mut model.conn, &session_ids)
+}
+
+#[tauri::command]
+fn get_local_ip() -> Result<String, String> {
+    let socket = UdpSocket::bind("0.0.0.0:0").map_err(|e| e.to_string())?;
+    socket.connect("8.8.8.8:80").map_err(|e| e.to_string())?;
+    Ok(socket
+        .local_addr()
+        .map_err(|e| e.to_string())?
+        .ip()
+        .to_string())
+}
 
-    emit_timer_state(&app, &timer);
-    Ok(settings)
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    tauri::Builder::default()
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_notification::init())
+        .setup(|app| {
+            let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+            fs::create_dir_all(&app_dir).map_err(|e| e.to_string())?;
+            let mut conn = open_database(&app_dir.join("pomodoro.db"))?;
+            // A reset can only be undone within the run that made it.
+            let _ = fs::remove_file(reset_snapshot_path(&conn)?);
+            let settings = load_or_create_settings(&conn)?;
+            purge_expired_trash(&mut conn, &settings, now_ts())?;
+            let (timer, plan, profile) = load_or_create_timer(&conn, &settings)?;
+
+            app.manage(AppState {
+                model: Mutex::new(AppModel {
+                    conn,
+                    settings,
+                    timer,
+                    plan,
+                    profile,
+                    reset_snapshot: None,
+                    recovered: None,
+                }),
+                remote: Mutex::new(RemoteControlState { server: None }),
+            });
+
+            setup_tray(app.handle())?;
+
+            {
+                let state = app.state::<AppState>();
+                let mut model = state.model.lock().map_err(|e| e.to_string())?;
+                // Record phases that ended while the app was closed before the UI loads,
+                // so it can pick up the result once it is ready.
+                match recover_expired_phase(&mut model, now_ts()) {
+                    Ok(Some(event)) => {
+                        notify_phase_recovered(app.handle(), &model, &event);
+                        model.recovered = Some(event);
+                    }
+                    Ok(None) => {}
+                    Err(error) => eprintln!("timer recovery warning: {error}"),
+                }
+                update_tray_title(app.handle(), &model.timer);
+            }
+
+            spawn_timer_worker(app.handle().clone());
+
+            // Remote control server (optional; disabled by default).
+            {
+                let state = app.state::<AppState>();
+                let model = state.model.lock().map_err(|e| e.to_string())?;
+                if let Err(error) = remote_apply(app.handle(), &model.settings) {
+                    eprintln!("remote control startup warning: {error}");
+                }
+            }
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            timer_start,
+            timer_pause,
+            timer_note_distraction,
+            timer_resume,
+            timer_skip,
+            timer_reset_phase,
+            timer_adjust,
+            timer_get_state,
+            timer_take_recovered,
+            timer_set_context,
+            session_complete,
+            analytics_get_summary,
+            analytics_get_timeseries,
+            analytics_get_interruptions,
+            analytics_get_tag_namespace,
+            analytics_get_ratings,
+            analytics_get_heatmap,
+            analytics_get_breakdown,
+            analytics_get_streaks,
+            projects_list,
+            projects_upsert,
+            tags_list,
+            tags_upsert,
+            projects_delete,
+            projects_merge,
+            tags_delete,
+            tags_merge,
+            tasks_list,
+            tasks_upsert,
+            tasks_reorder,
+            tasks_delete,
+            day_plan_get,
+            day_plan_set,
+            day_plan_report,
+            cycle_plans_list,
+            cycle_plans_upsert,
+            cycle_plans_delete,
+            timer_set_plan,
+            timer_profiles_list,
+            timer_profiles_upsert,
+            timer_profiles_delete,
+            timer_set_profile,
+            export_csv,
+            export_json,
+            settings_get,
+            settings_update,
+            reset_all_data,
+            reset_undo,
+            trash_list,
+            trash_restore,
+            trash_purge,
+            session_history,
+            session_search,
+            session_update,
+            session_delete,
+            session_rate,
+            get_local_ip,
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
 }
 
-#[tauri::command]
-fn reset_all_data(app: AppHandle, state: State<'_, AppState>) -> AppResult<ResetAllResult> {
-    let (settings, timer) = {
-        let mut model = lock_model(&state)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_settings() -> AppSettings {
+        AppSettings {
+            focus_min: 25,
+            short_break_min: 5,
+            long_break_min: 15,
+            long_break_every: 4,
+            theme: "light".to_string(),
+            sound_enabled: true,
+            notifications_enabled: true,
+            remote_control_enabled: false,
+            remote_control_port: 48484,
+            remote_control_token: "testtoken".to_string(),
+            ..AppSettings::default()
+        }
+    }
+
+    #[test]
+    fn advances_to_long_break_every_fourth_cycle() {
+        let settings = sample_settings();
+        let mut timer = TimerState::default_with_settings(&settings);
+
+        timer.phase = TimerPhase::Focus;
+        timer.cycle_index = 3;
+        advance_timer(&mut timer, &settings, None);
+
+        assert_eq!(timer.phase, TimerPhase::LongBreak);
+        assert_eq!(timer.cycle_index, 4);
+    }
+
+    fn focus_session(id: i64, started_at: i64, ended_at: i64) -> SessionRecord {
+        SessionRecord {
+            id,
+            started_at,
+            ended_at,
+            phase: TimerPhase::Focus,
+            duration_sec: 1500,
+            completed: true,
+            interruptions: 0,
+            project_id: None,
+            tag_ids: vec![],
+            counts_as_focus: true,
+            profile_id: None,
+            planned_duration_sec: 1500,
+            overtime_sec: 0,
+            adjusted_sec: 0,
+            pauses: vec![],
+            interruption_log: vec![],
+            note: None,
+            task_id: None,
+            focus_rating: None,
+            energy_level: None,
+            utc_offset_sec: None,
+        }
+    }
+
+    #[test]
+    fn auto_starts_breaks_and_stops_after_cycle_limit() {
+        let settings = AppSettings {
+            auto_start_breaks: true,
+            auto_start_focus: true,
+            auto_stop_after_cycles: 2,
+            ..sample_settings()
+        };
+        let mut timer = TimerState::default_with_settings(&settings);
+
+        for cycle in 1..=2 {
+            advance_timer(&mut timer, &settings, None);
+            schedule_auto_start(&mut timer, &settings, true);
+            assert!(timer.is_running, "break {cycle} should auto-start");
+            assert_eq!(timer.auto_run_cycles, cycle);
+
+            advance_timer(&mut timer, &settings, None);
+            schedule_auto_start(&mut timer, &settings, false);
+        }
+
+        assert_eq!(timer.phase, TimerPhase::Focus);
+        assert!(!timer.is_running, "focus after the cycle limit stays idle");
+        assert_eq!(timer.auto_run_cycles, 0);
+    }
+
+    #[test]
+    fn auto_start_waits_for_grace_period() {
+        let settings = AppSettings {
+            auto_start_breaks: true,
+            auto_start_delay_sec: 10,
+            ..sample_settings()
+        };
+        let mut timer = TimerState::default_with_settings(&settings);
+        advance_timer(&mut timer, &settings, None);
+        schedule_auto_start(&mut timer, &settings, true);
+
+        assert!(!timer.is_running);
+        assert!(timer.auto_start_at.is_some_and(|at| at > now_ts()));
+    }
+
+    #[test]
+    fn overtime_is_recorded_separately_from_planned_duration() {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+
+        let settings = sample_settings();
+        let mut timer = TimerState::default_with_settings(&settings);
+        begin_running(&mut timer);
+        timer.target_ends_at = Some(now_ts() - 90);
+        timer.in_overtime = true;
+        refresh_remaining(&mut timer);
+        assert_eq!(timer.remaining_seconds, 0);
+        assert!(timer.overtime_seconds >= 90);
+
+        // Pausing and resuming keeps the overtime already accrued.
+        timer.is_running = false;
+        timer.target_ends_at = None;
+        let paused_overtime = timer.overtime_seconds;
+        begin_running(&mut timer);
+        refresh_remaining(&mut timer);
+        assert!(timer.overtime_seconds >= paused_overtime);
+        assert_eq!(timer.remaining_seconds, 0);
+
+        let session = record_session(&conn, &timer, true, now_ts()).expect("record");
+        assert_eq!(session.planned_duration_sec, 25 * 60);
+        assert_eq!(session.overtime_sec, timer.overtime_seconds);
+        assert_eq!(session.duration_sec, 25 * 60 + timer.overtime_seconds);
+
+        advance_timer(&mut timer, &settings, None);
+        assert!(!timer.in_overtime);
+        assert_eq!(timer.overtime_seconds, 0);
+    }
+
+    #[test]
+    fn adjusting_shifts_remaining_and_target_within_bounds() {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+
+        let mut timer = TimerState::default_with_settings(&sample_settings());
+        begin_running(&mut timer);
+        let target = timer.target_ends_at.expect("running timer has a target");
+
+        assert_eq!(adjust_timer(&mut timer, 300), Ok(300));
+        assert_eq!(timer.target_ends_at, Some(target + 300));
+        assert_eq!(timer.phase_total_seconds, 30 * 60);
+
+        // Shortening can never push the phase below one remaining second.
+        let applied = adjust_timer(&mut timer, -3600).expect("adjust");
+        assert!(timer.remaining_seconds >= 1);
+        assert_eq!(timer.adjusted_seconds, 300 + applied);
+        assert!(adjust_timer(&mut timer, 2 * 3600).is_err());
+
+        let session = record_session(&conn, &timer, false, now_ts()).expect("record");
+        assert_eq!(session.adjusted_sec, timer.adjusted_seconds);
+        assert_eq!(session.planned_duration_sec, timer.phase_total_seconds);
+    }
+
+    #[test]
+    fn phases_that_ended_while_closed_are_recovered() {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+
+        let settings = sample_settings();
+        let mut timer = TimerState::default_with_settings(&settings);
+        begin_running(&mut timer);
+        let target = now_ts() - 3 * 3600;
+        timer.started_at = Some(target - timer.phase_total_seconds);
+        timer.target_ends_at = Some(target);
+
+        // Loading keeps the expired timer around instead of resetting it.
+        let timer = normalize_timer_state(timer, &settings, None);
+        assert!(timer.is_running);
+
+        let mut model = AppModel {
+            conn,
+            settings,
+            timer,
+            plan: None,
+            profile: None,
+            reset_snapshot: None,
+            recovered: None,
+        };
+        let event = recover_expired_phase(&mut model, now_ts())
+            .expect("recover")
+            .expect("expired phase is recovered");
+        assert_eq!(event.session.ended_at, target);
+        assert!(event.session.completed);
+        assert_eq!(event.session.duration_sec, 25 * 60);
+        assert_eq!(event.phase.completed_phase, TimerPhase::Focus);
+        assert_eq!(event.phase.next_phase, TimerPhase::ShortBreak);
+        assert_eq!(event.phase.rate_session_id, Some(event.session.id));
+        // Completion and recovery prompt for a rating under the same rule.
+        let empty = SessionRecord {
+            duration_sec: 0,
+            ..event.session.clone()
+        };
+        assert_eq!(rating_prompt_for(&empty), None);
+
+        assert!(!model.timer.is_running);
+        assert_eq!(model.timer.auto_start_at, None);
+        assert_eq!(model.timer.cycle_index, 1);
+        assert!(recover_expired_phase(&mut model, now_ts())
+            .expect("recover")
+            .is_none());
+    }
+
+    #[test]
+    fn reset_phase_restores_the_untouched_phase() {
+        let mut timer = TimerState::default_with_settings(&sample_settings());
+        begin_running(&mut timer);
+        adjust_timer(&mut timer, 120).expect("adjust");
+        timer.interruptions = 2;
+
+        reset_phase(&mut timer);
+        assert!(!timer.is_running);
+        assert_eq!(timer.started_at, None);
+        assert_eq!(timer.target_ends_at, None);
+        assert_eq!(timer.interruptions, 0);
+        assert_eq!(timer.adjusted_seconds, 0);
+        assert_eq!(timer.phase_total_seconds, 25 * 60);
+        assert_eq!(timer.remaining_seconds, timer.phase_total_seconds);
+    }
+
+    #[test]
+    fn adjustments_survive_a_restart() {
+        let settings = sample_settings();
+        let mut timer = TimerState::default_with_settings(&settings);
+        begin_running(&mut timer);
+        adjust_timer(&mut timer, 600).expect("adjust");
+        pause_timer(&mut timer, None);
+
+        // Loading the saved state again must neither drop nor double the adjustment.
+        let mut timer = normalize_timer_state(timer, &settings, None);
+        assert_eq!(timer.phase_total_seconds, 35 * 60);
+        assert!(timer.remaining_seconds > 25 * 60);
+        assert!(timer.started_at.is_some());
+
+        reset_phase(&mut timer);
+        assert_eq!(timer.phase_total_seconds, 25 * 60);
+        assert_eq!(timer.remaining_seconds, 25 * 60);
+    }
+
+    #[test]
+    fn sessions_can_be_edited_and_deleted() {
+        let mut conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+
+        let mut timer = TimerState::default_with_settings(&sample_settings());
+        timer.started_at = Some(1_000);
+        timer.phase_total_seconds = 3 * 3600;
+        let long = record_session(&conn, &timer, true, 1_000 + 3 * 3600).expect("record");
+        timer.started_at = Some(20_000);
+        let other = record_session(&conn, &timer, true, 20_000 + 3 * 3600).expect("record");
+
+        let request = |body: serde_json::Value| {
+            serde_json::from_value::<UpdateSessionRequest>(body).expect("request")
+        };
+        let edited = update_session(
+            &mut conn,
+            request(serde_json::json!({ "id": long.id, "endedAt": 1_000 + 1_500 })),
+        )
+        .expect("update");
+        assert_eq!(edited.duration_sec, 1_500);
+        assert!(edited.counts_as_focus);
+
+        let edited = update_session(
+            &mut conn,
+            request(
+                serde_json::json!({ "id": long.id, "phase": "short_break", "projectId": null }),
+            ),
+        )
+        .expect("update");
+        assert_eq!(edited.phase, TimerPhase::ShortBreak);
+        assert!(!edited.counts_as_focus);
+
+        for invalid in [
+            serde_json::json!({ "id": long.id, "endedAt": 500 }),
+            serde_json::json!({ "id": long.id, "durationSec": -1 }),
+            serde_json::json!({ "id": long.id, "tagIds": [42] }),
+            serde_json::json!({ "id": 999, "completed": false }),
+        ] {
+            assert!(update_session(&mut conn, request(invalid)).is_err());
+        }
+
+        // Breaks never carry a project, even one that exists.
+        conn.execute(
+            "INSERT INTO projects (name, color, created_at) VALUES ('Work', '#000000', 0)",
+            [],
+        )
+        .expect("project");
+        let project_id = conn.last_insert_rowid();
+        let error = update_session(
+            &mut conn,
+            request(serde_json::json!({ "id": long.id, "projectId": project_id })),
+        )
+        .expect_err("break with project");
+        assert_eq!(error.code, SessionErrorCode::InvalidField);
+        assert_eq!(error.field.as_deref(), Some("projectId"));
+
+        let moved = update_session(
+            &mut conn,
+            request(serde_json::json!({ "id": long.id, "startedAt": 19_000, "endedAt": 21_000 })),
+        )
+        .expect_err("overlap");
+        assert_eq!(moved.code, SessionErrorCode::Overlap);
+        assert_eq!(moved.conflicting_session_ids, vec![other.id]);
+
+        assert_eq!(delete_sessions(&mut conn, &[long.id, other.id, 999]), Ok(2));
+        assert!(fetch_sessions(&conn, &AnalyticsRange::default())
+            .expect("fetch")
+            .is_empty());
+    }
+
+    #[test]
+    fn logged_sessions_are_validated_and_resolve_overlaps() {
+        let mut conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+        let now = 1_000_000;
+        let entry = |started_at: i64, ended_at: i64, overlap: &str| {
+            serde_json::from_value::<CompleteSessionRequest>(serde_json::json!({
+                "startedAt": started_at,
+                "endedAt": ended_at,
+                "phase": "focus",
+                "durationSec": ended_at - started_at,
+                "completed": true,
+                "interruptions": 0,
+                "projectId": null,
+                "tagIds": null,
+                "overlap": overlap,
+            }))
+            .expect("request")
+        };
+
+        let first = log_session(&mut conn, entry(10_000, 11_500, "reject"), now).expect("log");
+        let second = log_session(&mut conn, entry(12_000, 13_500, "reject"), now).expect("log");
+
+        let error = log_session(&mut conn, entry(11_000, 12_500, "reject"), now).unwrap_err();
+        assert_eq!(error.code, SessionErrorCode::Overlap);
+        assert_eq!(error.conflicting_session_ids, vec![first.id, second.id]);
+
+        let error = log_session(&mut conn, entry(now, now + 3_600, "reject"), now).unwrap_err();
+        assert_eq!(error.field.as_deref(), Some("endedAt"));
+        let mut tagged = entry(20_000, 21_000, "reject");
+        tagged.tag_ids = Some(vec![7]);
+        let error = log_session(&mut conn, tagged, now).unwrap_err();
+        assert_eq!(error.code, SessionErrorCode::NotFound);
+
+        log_session(&mut conn, entry(11_000, 12_500, "trim"), now).expect("trim");
+        let first = fetch_session(&conn, first.id)
+            .expect("fetch")
+            .expect("kept");
+        assert_eq!((first.ended_at, first.duration_sec), (11_000, 1_000));
+        let second = fetch_session(&conn, second.id)
+            .expect("fetch")
+            .expect("kept");
+        assert_eq!((second.started_at, second.duration_sec), (12_500, 1_000));
+
+        let error = log_session(&mut conn, entry(12_600, 13_000, "trim"), now).unwrap_err();
+        assert_eq!(error.code, SessionErrorCode::Overlap);
+        assert_eq!(error.conflicting_session_ids, vec![second.id]);
+
+        log_session(&mut conn, entry(9_000, 14_000, "replace"), now).expect("replace");
+        let sessions = fetch_sessions(&conn, &AnalyticsRange::default()).expect("fetch");
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].started_at, 9_000);
+
+        // Without a policy, overlaps are accepted as they were before.
+        let mut plain = entry(9_500, 10_000, "reject");
+        plain.overlap = OverlapPolicy::default();
+        log_session(&mut conn, plain, now).expect("accept");
+        assert_eq!(
+            fetch_sessions(&conn, &AnalyticsRange::default())
+                .expect("fetch")
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn session_notes_are_searchable_within_a_range() {
+        let mut conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+
+        let mut timer = TimerState::default_with_settings(&sample_settings());
+        timer.session_note = normalize_session_note(Some("  Refactored the café parser ".into()));
+        let early = record_session(&conn, &timer, true, 10_000).expect("record");
+        timer.session_note = Some("Parser tests and \"quotes\"".to_string());
+        let late = record_session(&conn, &timer, true, 50_000).expect("record");
+        timer.session_note = None;
+        record_session(&conn, &timer, true, 60_000).expect("record");
+        assert_eq!(early.note.as_deref(), Some("Refactored the café parser"));
+
+        let ids = |conn: &Connection, text: &str, range: &AnalyticsRange| -> Vec<i64> {
+            search_sessions(conn, text, range)
+                .expect("search")
+                .iter()
+                .map(|session| session.id)
+                .collect()
+        };
+        let everything = AnalyticsRange::default();
+        let mut found = ids(&conn, "pars", &everything);
+        found.sort_unstable();
+        assert_eq!(found, vec![early.id, late.id]);
+        assert_eq!(ids(&conn, "cafe", &everything), vec![early.id]);
+        assert_eq!(ids(&conn, "\"quotes", &everything), vec![late.id]);
+        let recent = AnalyticsRange {
+            from: Some(20_000),
+            ..Default::default()
+        };
+        assert_eq!(ids(&conn, "parser", &recent), vec![late.id]);
+        assert_eq!(ids(&conn, "   ", &recent).len(), 2);
+
+        let request = serde_json::from_value::<UpdateSessionRequest>(
+            serde_json::json!({ "id": late.id, "note": "Wrote docs" }),
+        )
+        .expect("request");
+        update_session(&mut conn, request).expect("update");
+        assert!(ids(&conn, "parser", &recent).is_empty());
+        assert_eq!(ids(&conn, "docs", &everything), vec![late.id]);
+
+        delete_sessions(&mut conn, &[late.id]).expect("delete");
+        assert!(ids(&conn, "docs", &everything).is_empty());
+    }
+
+    #[test]
+    fn tasks_track_estimated_and_actual_pomodoros() {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+        conn.execute(
+            "INSERT INTO projects (name, created_at) VALUES ('Work', 0)",
+            [],
+        )
+        .expect("project");
+        let project_id = conn.last_insert_rowid();
+
+        let input = |id: Option<i64>, title: &str, status: TaskStatus| TaskInput {
+            id,
+            project_id: Some(project_id),
+            title: title.to_string(),
+            estimate_pomodoros: Some(3),
+            status,
+        };
+        let first =
+            upsert_task(&conn, input(None, " Write spec ", TaskStatus::Todo)).expect("task");
+        let second = upsert_task(&conn, input(None, "Review", TaskStatus::Todo)).expect("task");
+        assert_eq!(first.title, "Write spec");
+        assert_eq!((first.position, second.position), (0, 1));
+        assert!(upsert_task(&conn, input(None, "  ", TaskStatus::Todo)).is_err());
+
+        let settings = sample_settings();
+        let mut timer = TimerState::default_with_settings(&settings);
+        timer.current_task_id = Some(first.id);
+        let focus = record_session(&conn, &timer, true, 10_000).expect("record");
+        record_session(&conn, &timer, false, 20_000).expect("record");
+        advance_timer(&mut timer, &settings, None);
+        let short_break = record_session(&conn, &timer, true, 30_000).expect("record");
+        assert_eq!(focus.task_id, Some(first.id));
+        assert_eq!(short_break.task_id, None);
+
+        let first = fetch_task(&conn, first.id).expect("fetch").expect("task");
+        assert_eq!(first.estimate_pomodoros, Some(3));
+        assert_eq!(first.actual_pomodoros, 1);
+        assert_eq!(first.focus_seconds, 25 * 60);
+
+        let done =
+            upsert_task(&conn, input(Some(second.id), "Review", TaskStatus::Done)).expect("done");
+        assert!(done.completed_at.is_some());
+        let open = fetch_tasks(&conn, Some(project_id), false).expect("tasks");
+        assert_eq!(open.len(), 1);
+        assert_eq!(fetch_tasks(&conn, None, true).expect("tasks").len(), 2);
+
+        conn.execute("DELETE FROM tasks WHERE id = ?1", params![first.id])
+            .expect("delete");
+        let session = fetch_session(&conn, focus.id)
+            .expect("fetch")
+            .expect("session");
+        assert_eq!(session.task_id, None);
+    }
+
+    #[test]
+    fn day_plan_queue_drives_focus_context_and_reports() {
+        let mut conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+        conn.execute_batch(
+            "INSERT INTO projects (name, created_at) VALUES ('Work', 0), ('Study', 0);
+             INSERT INTO tags (name, created_at) VALUES ('deep', 0);",
+        )
+        .expect("seed");
+        let task = upsert_task(
+            &conn,
+            TaskInput {
+                id: None,
+                project_id: Some(2),
+                title: "Read chapter".to_string(),
+                estimate_pomodoros: None,
+                status: TaskStatus::Todo,
+            },
+        )
+        .expect("task");
+
+        // A fixed time keeps every step of the test on the same day.
+        let now = Local
+            .with_ymd_and_hms(2024, 5, 6, 12, 0, 0)
+            .single()
+            .expect("local time")
+            .timestamp();
+        let today = day_key(now, 0);
+        let items = set_day_plan(
+            &mut conn,
+            &today,
+            vec![
+                DayPlanItemInput {
+                    id: None,
+                    task_id: None,
+                    project_id: Some(1),
+                    tag_ids: Some(vec![1]),
+                    planned_pomodoros: 2,
+                },
+                DayPlanItemInput {
+                    id: None,
+                    task_id: Some(task.id),
+                    project_id: None,
+                    tag_ids: None,
+                    planned_pomodoros: 1,
+                },
+            ],
+        )
+        .expect("plan");
+        assert_eq!(items[1].project_id, Some(2));
+        assert_eq!(items[1].title.as_deref(), Some("Read chapter"));
+
+        let mut timer = TimerState::default_with_settings(&sample_settings());
+        select_day_plan_item(&conn, &mut timer, now, 0).expect("select");
+        assert_eq!(timer.day_plan_item_id, Some(items[0].id));
+        assert_eq!(timer.current_project_id, Some(1));
+        assert_eq!(timer.current_tag_ids, vec![1]);
+
+        for _ in 0..2 {
+            record_session(&conn, &timer, true, now).expect("record");
+            advance_day_plan(&conn, &mut timer, now, now, 0).expect("advance");
+        }
+        assert_eq!(timer.day_plan_item_id, Some(items[1].id));
+        assert_eq!(timer.current_task_id, Some(task.id));
+        assert!(timer.current_tag_ids.is_empty());
+
+        // A focus phase that ended on another day does not count against today's item.
+        let item_id = timer.day_plan_item_id;
+        advance_day_plan(&conn, &mut timer, now - 2 * 86_400, now, 0).expect("advance");
+        assert_eq!(timer.day_plan_item_id, item_id);
+
+        record_session(&conn, &timer, true, now).expect("record");
+        advance_day_plan(&conn, &mut timer, now, now, 0).expect("advance");
+        record_session(&conn, &timer, true, now).expect("record");
+        assert_eq!(timer.day_plan_item_id, None);
+
+        let report = day_plan_report_for(&conn, &today, 0).expect("report");
+        assert_eq!(report.planned_pomodoros, 3);
+        assert_eq!(report.completed_pomodoros, 3);
+        assert_eq!(report.focus_pomodoros, 4);
+        assert_eq!(report.unplanned_pomodoros, 1);
+
+        // Re-saving keeps progress on existing items.
+        let kept = set_day_plan(
+            &mut conn,
+            &today,
+            vec![DayPlanItemInput {
+                id: Some(items[1].id),
+                task_id: Some(task.id),
+                project_id: None,
+                tag_ids: None,
+                planned_pomodoros: 2,
+            }],
+        )
+        .expect("plan");
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].completed_pomodoros, 1);
+        assert!(set_day_plan(&mut conn, "yesterday", Vec::new()).is_err());
+
+        let mut model = AppModel {
+            conn,
+            settings: sample_settings(),
+            timer,
+            plan: None,
+            profile: None,
+            reset_snapshot: None,
+            recovered: None,
+        };
+        select_day_plan_item(&model.conn, &mut model.timer, now, 0).expect("select");
+        assert_eq!(model.timer.day_plan_item_id, Some(items[1].id));
+        let context = |project_id: i64| StartTimerRequest {
+            project_id: Some(Some(project_id)),
+            tag_ids: None,
+            note: None,
+            task_id: None,
+        };
+        apply_timer_context(&mut model, context(2)).expect("same context");
+        assert_eq!(model.timer.day_plan_item_id, Some(items[1].id));
+        apply_timer_context(&mut model, context(1)).expect("override");
+        assert_eq!(model.timer.day_plan_item_id, None);
+    }
+
+    #[test]
+    fn projects_and_tags_merge_or_detach_on_delete() {
+        let mut conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+        conn.execute_batch(
+            "INSERT INTO projects (name, created_at) VALUES ('Work', 0), ('Wrk', 0), ('Misc', 0);
+             INSERT INTO tags (name, created_at) VALUES ('bug', 0), ('bugs', 0), ('misc', 0);",
+        )
+        .expect("seed");
+
+        let mut timer = TimerState::default_with_settings(&sample_settings());
+        timer.current_project_id = Some(2);
+        timer.current_tag_ids = vec![1, 2];
+        let both = record_session(&conn, &timer, true, 10_000).expect("record");
+        timer.current_project_id = Some(3);
+        timer.current_tag_ids = vec![2, 3];
+        let misc = record_session(&conn, &timer, true, 20_000).expect("record");
+
+        assert!(remove_project(&mut conn, 2, Some(2)).is_err());
+        assert!(remove_project(&mut conn, 2, Some(99)).is_err());
+        remove_project(&mut conn, 2, Some(1)).expect("merge project");
+        remove_project(&mut conn, 3, None).expect("delete project");
+        remove_tag(&mut conn, 2, Some(1)).expect("merge tag");
+        remove_tag(&mut conn, 3, None).expect("delete tag");
+
+        let session = |id: i64| fetch_session(&conn, id).expect("fetch").expect("session");
+        assert_eq!(session(both.id).project_id, Some(1));
+        assert_eq!(session(both.id).tag_ids, vec![1]);
+        assert_eq!(session(misc.id).project_id, None);
+        assert_eq!(session(misc.id).tag_ids, vec![1]);
+        assert_eq!(fetch_projects(&conn).expect("projects").len(), 1);
+        assert_eq!(fetch_tags(&conn).expect("tags").len(), 1);
+
+        assert!(retarget_timer_context(
+            &mut timer,
+            Some((3, None)),
+            Some((2, Some(1)))
+        ));
+        assert_eq!(timer.current_project_id, None);
+        assert_eq!(timer.current_tag_ids, vec![3, 1]);
+        assert!(!retarget_timer_context(&mut timer, Some((3, None)), None));
+
+        // Renaming an archived tag leaves it archived unless `archived` is sent.
+        conn.execute("UPDATE tags SET archived = 1 WHERE id = 1", [])
+            .expect("archive");
+        let renamed = upsert_tag(&mut conn, tag_input(Some(1), "defect")).expect("rename");
+        assert_eq!(renamed.name, "defect");
+        assert!(renamed.archived);
+        let restored = upsert_tag(
+            &mut conn,
+            TagInput {
+                archived: Some(false),
+                ..tag_input(Some(1), "defect")
+            },
+        )
+        .expect("unarchive");
+        assert!(!restored.archived);
+    }
+
+    #[test]
+    fn project_filters_roll_up_subprojects() {
+        let mut conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+        conn.execute_batch(
+            "INSERT INTO projects (name, created_at, parent_id) VALUES ('Acme', 0, NULL);
+             INSERT INTO projects (name, created_at, parent_id) VALUES ('Website', 0, 1);
+             INSERT INTO projects (name, created_at, parent_id) VALUES ('Backend', 0, 2);",
+        )
+        .expect("seed");
+
+        let mut timer = TimerState::default_with_settings(&sample_settings());
+        timer.current_project_id = Some(2);
+        record_session(&conn, &timer, true, 10_000).expect("record");
+        timer.current_project_id = Some(3);
+        record_session(&conn, &timer, true, 20_000).expect("record");
+
+        let count = |conn: &Connection, include_subprojects: bool| {
+            fetch_sessions(
+                conn,
+                &AnalyticsRange {
+                    project_id: Some(1),
+                    include_subprojects,
+                    ..Default::default()
+                },
+            )
+            .expect("fetch")
+            .len()
+        };
+        assert_eq!(count(&conn, false), 0);
+        assert_eq!(count(&conn, true), 2);
+
+        let backend = fetch_project(&conn, 3).expect("fetch").expect("project");
+        assert_eq!(backend.path, "Acme / Website / Backend");
+        assert_eq!(project_subtree_ids(&conn, 2).expect("subtree"), vec![2, 3]);
+
+        // Updates that leave out `parentId` keep the project where it is.
+        let input =
+            |body: serde_json::Value| serde_json::from_value::<ProjectInput>(body).expect("input");
+        let renamed = upsert_project(&conn, input(serde_json::json!({ "id": 3, "name": "API" })))
+            .expect("rename");
+        assert_eq!(renamed.parent_id, Some(2));
+        assert!(upsert_project(
+            &conn,
+            input(serde_json::json!({ "id": 1, "name": "Acme", "parentId": 3 }))
+        )
+        .is_err());
+        let moved = upsert_project(
+            &conn,
+            input(serde_json::json!({ "id": 3, "name": "Backend", "parentId": null })),
+        )
+        .expect("move to top level");
+        assert_eq!(moved.parent_id, None);
+        upsert_project(
+            &conn,
+            input(serde_json::json!({ "id": 3, "name": "Backend", "parentId": 2 })),
+        )
+        .expect("move back");
+
+        remove_project(&mut conn, 2, None).expect("delete");
+        let backend = fetch_project(&conn, 3).expect("fetch").expect("project");
+        assert_eq!(backend.parent_id, Some(1));
+        assert_eq!(backend.path, "Acme / Backend");
+        assert_eq!(count(&conn, true), 1);
+
+        // Restoring puts the tree back together; purging keeps the subproject under Acme.
+        restore_trashed(&mut conn, TrashKind::Project, 2).expect("restore");
+        let backend = fetch_project(&conn, 3).expect("fetch").expect("project");
+        assert_eq!(backend.path, "Acme / Website / Backend");
+        assert_eq!(count(&conn, true), 2);
+        remove_project(&mut conn, 2, None).expect("delete");
+        purge_trash(&mut conn, Some((TrashKind::Project, 2)), None).expect("purge");
+        let parent_id: Option<i64> = conn
+            .query_row("SELECT parent_id FROM projects WHERE id = 3", [], |row| {
+                row.get(0)
+            })
+            .expect("parent");
+        assert_eq!(parent_id, Some(1));
+    }
+
+    #[test]
+    fn nested_tags_roll_up_into_their_namespace() {
+        let mut conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+
+        let bug = upsert_tag(&mut conn, tag_input(None, " type / bug ")).expect("bug");
+        assert_eq!(bug.name, "type/bug");
+        let feature = upsert_tag(&mut conn, tag_input(None, "type/feature")).expect("feature");
+        let namespace = bug.parent_id.expect("parent created");
+        assert_eq!(feature.parent_id, Some(namespace));
+        assert!(upsert_tag(&mut conn, tag_input(None, "type//bug")).is_err());
+        assert!(upsert_tag(&mut conn, tag_input(Some(namespace), "type/bug/type")).is_err());
+
+        let mut timer = TimerState::default_with_settings(&sample_settings());
+        timer.current_tag_ids = vec![bug.id];
+        record_session(&conn, &timer, true, 10_000).expect("record");
+        timer.current_tag_ids = vec![feature.id];
+        record_session(&conn, &timer, true, 20_000).expect("record");
+        record_session(&conn, &timer, true, 30_000).expect("record");
+
+        let range = AnalyticsRange {
+            tag_id: Some(namespace),
+            ..Default::default()
+        };
+        assert_eq!(fetch_sessions(&conn, &range).expect("fetch").len(), 3);
+        let stats = tag_namespace_breakdown(&conn, &range, namespace).expect("breakdown");
+        let names: Vec<_> = stats
+            .iter()
+            .map(|stat| (stat.name.as_str(), stat.sessions))
+            .collect();
+        assert_eq!(names, vec![("type/feature", 2), ("type/bug", 1)]);
+
+        assert!(remove_tag(&mut conn, namespace, None).is_err());
+        let renamed = upsert_tag(&mut conn, tag_input(Some(namespace), "kind")).expect("rename");
+        assert_eq!(renamed.parent_id, None);
+        let names: Vec<_> = fetch_tags(&conn)
+            .expect("tags")
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect();
+        assert_eq!(names, vec!["kind", "kind/bug", "kind/feature"]);
+    }
+
+    fn tag_input(id: Option<i64>, name: &str) -> TagInput {
+        TagInput {
+            id,
+            name: name.to_string(),
+            archived: None,
+        }
+    }
+
+    /// Schema and data as written by the first release, before versioning existed.
+    const BASELINE_FIXTURE: &str = r#"
+        CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+        CREATE TABLE projects (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            color TEXT,
+            archived INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL
+        );
+        CREATE TABLE tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            created_at INTEGER NOT NULL
+        );
+        CREATE TABLE sessions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            started_at INTEGER NOT NULL,
+            ended_at INTEGER NOT NULL,
+            phase TEXT NOT NULL,
+            duration_sec INTEGER NOT NULL,
+            completed INTEGER NOT NULL,
+            interruptions INTEGER NOT NULL DEFAULT 0,
+            project_id INTEGER
+        );
+        CREATE TABLE session_tags (
+            session_id INTEGER NOT NULL,
+            tag_id INTEGER NOT NULL,
+            PRIMARY KEY (session_id, tag_id)
+        );
+        INSERT INTO projects (name, created_at) VALUES ('Work', 0);
+        INSERT INTO tags (name, created_at) VALUES ('deep', 0);
+        INSERT INTO sessions (started_at, ended_at, phase, duration_sec, completed, project_id)
+            VALUES (1000, 2500, 'focus', 1500, 1, 1), (2500, 2800, 'short_break', 300, 1, NULL);
+        INSERT INTO session_tags (session_id, tag_id) VALUES (1, 1);
+    "#;
+
+    #[test]
+    fn migrations_upgrade_older_schemas() {
+        let baseline = Connection::open_in_memory().expect("in-memory db");
+        baseline.execute_batch(BASELINE_FIXTURE).expect("fixture");
+        init_database(&baseline).expect("upgrade baseline");
+        assert_eq!(schema_version(&baseline).expect("version"), SCHEMA_VERSION);
+        let sessions = fetch_sessions(&baseline, &AnalyticsRange::default()).expect("fetch");
+        assert_eq!(sessions.len(), 2);
+        let focus = sessions
+            .iter()
+            .find(|s| s.phase == TimerPhase::Focus)
+            .expect("focus");
+        assert!(focus.counts_as_focus);
+        assert_eq!(focus.planned_duration_sec, 1500);
+        assert_eq!(focus.tag_ids, vec![1]);
+        baseline
+            .execute("UPDATE sessions SET note = 'kept' WHERE id = 1", [])
+            .expect("note");
+        assert_eq!(
+            search_sessions(&baseline, "kept", &AnalyticsRange::default())
+                .expect("search")
+                .len(),
+            1
+        );
 
-        {
-            let tx = model.conn.transaction().map_err(|e| e.to_string())?;
-            tx.execute("DELETE FROM session_tags", [])
-                .map_err(|e| e.to_string())?;
-            tx.execute("DELETE FROM sessions", [])
-                .map_err(|e| e.to_string())?;
-            tx.execute("DELETE FROM projects", [])
-                .map_err(|e| e.to_string())?;
-            tx.execute("DELETE FROM tags", [])
-                .map_err(|e| e.to_string())?;
-            tx.execute("DELETE FROM settings", [])
-                .map_err(|e| e.to_string())?;
-            tx.execute(
-                "DELETE FROM sqlite_sequence WHERE name IN ('projects', 'tags', 'sessions')",
-                [],
-            )
-            .map_err(|e| e.to_string())?;
-            tx.commit().map_err(|e| e.to_string())?;
-        }
+        // An unversioned database that already had notes but no index gets one built.
+        let notes = Connection::open_in_memory().expect("in-memory db");
+        notes.execute_batch(BASELINE_FIXTURE).expect("fixture");
+        notes
+            .execute_batch("ALTER TABLE sessions ADD COLUMN note TEXT; UPDATE sessions SET note = 'legacy note' WHERE id = 1;")
+            .expect("notes column");
+        init_database(&notes).expect("upgrade notes");
+        assert_eq!(
+            search_sessions(&notes, "legacy", &AnalyticsRange::default())
+                .expect("search")
+                .len(),
+            1
+        );
 
-        model.settings = normalize_settings(AppSettings::default());
-        ensure_remote_token(&mut model.settings);
-        model.timer = TimerState::default_with_settings(&model.settings);
-        save_json_setting(&model.conn, APP_SETTINGS_KEY, &model.settings)?;
-        save_timer_state(&model.conn, &model.timer)?;
+        init_database(&notes).expect("re-running is a no-op");
+        assert_eq!(schema_version(&notes).expect("version"), SCHEMA_VERSION);
 
-        (model.settings.clone(), model.timer.clone())
-    };
+        // Tags that already used slashes join their namespace.
+        let slashed = Connection::open_in_memory().expect("in-memory db");
+        slashed.execute_batch(BASELINE_FIXTURE).expect("fixture");
+        slashed
+            .execute_batch(
+                "INSERT INTO tags (name, created_at) VALUES ('type/bug', 0), ('type/ux/copy', 0), ('a//b', 0);",
+            )
+            .expect("legacy tags");
+        init_database(&slashed).expect("upgrade slashed tags");
+        let namespace = tag_id_by_name(&slashed, "type")
+            .expect("lookup")
+            .expect("namespace created");
+        let tags = fetch_tags(&slashed).expect("tags");
+        let parent_of = |name: &str| {
+            tags.iter()
+                .find(|tag| tag.name == name)
+                .expect("tag")
+                .parent_id
+        };
+        assert_eq!(parent_of("type/bug"), Some(namespace));
+        assert_eq!(
+            parent_of("type/ux/copy"),
+            tag_id_by_name(&slashed, "type/ux").expect("lookup")
+        );
+        assert_eq!(parent_of("a//b"), None);
+    }
 
-    remote_apply(&app, &settings)?;
-    emit_timer_state(&app, &timer);
-    Ok(ResetAllResult { settings, timer })
-}
+    #[test]
+    fn newer_databases_are_refused_and_upgrades_are_backed_up() {
+        let newer = Connection::open_in_memory().expect("in-memory db");
+        newer
+            .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .expect("version");
+        let err = init_database(&newer).expect_err("newer schema");
+        assert!(err.contains("newer than this app supports"));
+
+        let dir = std::env::temp_dir().join(format!("pomodoro-migrate-{}", std::process::id()));
+        // Start clean so backups left behind by an earlier, aborted run are not counted.
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("temp dir");
+        let db_path = dir.join("pomodoro.db");
+        Connection::open(&db_path)
+            .expect("file db")
+            .execute_batch(BASELINE_FIXTURE)
+            .expect("fixture");
+
+        open_database(&db_path).expect("upgrade");
+        open_database(&db_path).expect("reopen");
+        let backups: Vec<_> = fs::read_dir(&dir)
+            .expect("list")
+            .map(|entry| entry.expect("entry").path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "bak"))
+            .collect();
+        assert_eq!(backups.len(), 1);
+        let backup = Connection::open(&backups[0]).expect("backup");
+        assert_eq!(schema_version(&backup).expect("version"), 0);
+        let count: i64 = backup
+            .query_row("SELECT COUNT(*) FROM sessions", [], |row| row.get(0))
+            .expect("count");
+        assert_eq!(count, 2);
+        drop(backup);
+        fs::remove_dir_all(&dir).expect("cleanup");
+    }
 
-#[tauri::command]
-fn session_history(
-    range: AnalyticsRange,
-    state: State<'_, AppState>,
-) -> AppResult<Vec<SessionRecord>> {
-    let model = lock_model(&state)?;
-    fetch_sessions(&model.conn, &range)
-}
+    #[test]
+    fn deleted_items_go_to_the_trash_until_purged() {
+        let mut conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+        conn.execute_batch(
+            "INSERT INTO projects (name, created_at) VALUES ('Work', 0);
+             INSERT INTO tags (name, created_at) VALUES ('deep', 0);",
+        )
+        .expect("seed");
+
+        let mut timer = TimerState::default_with_settings(&sample_settings());
+        timer.current_project_id = Some(1);
+        timer.current_tag_ids = vec![1];
+        let first = record_session(&conn, &timer, true, 10_000).expect("record");
+        let second = record_session(&conn, &timer, true, 20_000).expect("record");
+        let now = Local
+            .with_ymd_and_hms(2024, 5, 6, 12, 0, 0)
+            .single()
+            .expect("local time")
+            .timestamp();
+        let today = day_key(now, 0);
+        let plan_item = DayPlanItemInput {
+            id: None,
+            task_id: None,
+            project_id: Some(1),
+            tag_ids: None,
+            planned_pomodoros: 1,
+        };
+        set_day_plan(&mut conn, &today, vec![plan_item]).expect("plan");
 
-#[tauri::command]
-fn get_local_ip() -> Result<String, String> {
-    let socket = UdpSocket::bind("0.0.0.0:0").map_err(|e| e.to_string())?;
-    socket.connect("8.8.8.8:80").map_err(|e| e.to_string())?;
-    Ok(socket
-        .local_addr()
-        .map_err(|e| e.to_string())?
-        .ip()
-        .to_string())
-}
+        assert_eq!(delete_sessions(&mut conn, &[first.id]).expect("delete"), 1);
+        remove_project(&mut conn, 1, None).expect("trash project");
+        remove_tag(&mut conn, 1, None).expect("trash tag");
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::Builder::default()
-        .plugin(tauri_plugin_opener::init())
-        .plugin(tauri_plugin_dialog::init())
-        .plugin(tauri_plugin_fs::init())
-        .plugin(tauri_plugin_notification::init())
-        .setup(|app| {
-            let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-            fs::create_dir_all(&app_dir).map_err(|e| e.to_string())?;
-            let db_path = app_dir.join("pomodoro.db");
-            let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+        // Day plans do not hand a trashed project back to the timer.
+        let planned = fetch_day_plan(&conn, &today).expect("day plan");
+        assert_eq!(
+            (planned[0].project_id, planned[0].title.as_deref()),
+            (None, None)
+        );
+        let mut planned_timer = TimerState::default_with_settings(&sample_settings());
+        select_day_plan_item(&conn, &mut planned_timer, now, 0).expect("select");
+        assert_eq!(planned_timer.current_project_id, None);
+
+        let live = fetch_sessions(&conn, &AnalyticsRange::default()).expect("fetch");
+        assert_eq!(live.len(), 1);
+        assert_eq!(live[0].project_id, None);
+        assert!(live[0].tag_ids.is_empty());
+        assert!(fetch_projects(&conn).expect("projects").is_empty());
+        let kinds: Vec<_> = fetch_trash(&conn)
+            .expect("trash")
+            .into_iter()
+            .map(|item| (item.kind, item.id))
+            .collect();
+        assert_eq!(kinds.len(), 3);
+        assert!(kinds.contains(&(TrashKind::Session, first.id)));
+
+        let err = upsert_tag(&mut conn, tag_input(None, "deep")).expect_err("name held by trash");
+        assert!(err.contains("in the trash"));
+
+        restore_trashed(&mut conn, TrashKind::Project, 1).expect("restore project");
+        restore_trashed(&mut conn, TrashKind::Tag, 1).expect("restore tag");
+        assert!(restore_trashed(&mut conn, TrashKind::Tag, 1).is_err());
+        let restored = fetch_session(&conn, second.id)
+            .expect("fetch")
+            .expect("session");
+        assert_eq!(restored.project_id, Some(1));
+        assert_eq!(restored.tag_ids, vec![1]);
+
+        let mut settings = sample_settings();
+        settings.trash_retention_days = 1;
+        let deleted_at = fetch_trash(&conn).expect("trash")[0].deleted_at;
+        assert_eq!(
+            purge_expired_trash(&mut conn, &settings, deleted_at + 3_600).expect("purge"),
+            0
+        );
+        assert_eq!(
+            purge_expired_trash(&mut conn, &settings, deleted_at + 2 * 86_400).expect("purge"),
+            1
+        );
+        assert!(fetch_trash(&conn).expect("trash").is_empty());
+        assert!(restore_trashed(&mut conn, TrashKind::Session, first.id).is_err());
+    }
 
-            init_database(&conn)?;
-            let settings = load_or_create_settings(&conn)?;
-            let timer = load_or_create_timer(&conn, &settings)?;
+    #[test]
+    fn reset_snapshot_restores_all_user_data() {
+        let dir = std::env::temp_dir().join(format!("pomodoro-reset-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("temp dir");
+        let conn = open_database(&dir.join("pomodoro.db")).expect("open");
+        conn.execute_batch("INSERT INTO projects (name, created_at) VALUES ('Work', 0);")
+            .expect("seed");
+        let mut timer = TimerState::default_with_settings(&sample_settings());
+        timer.current_project_id = Some(1);
+        timer.session_note = Some("snapshot me".to_string());
+        record_session(&conn, &timer, true, 10_000).expect("record");
+        save_timer_state(&conn, &timer).expect("save timer");
+
+        let snapshot = reset_snapshot_path(&conn).expect("snapshot path");
+        backup_database(&conn, &snapshot).expect("snapshot");
+        conn.execute_batch("DELETE FROM sessions; DELETE FROM projects; DELETE FROM settings;")
+            .expect("reset");
+
+        // Anything written after the reset blocks the undo instead of being dropped.
+        let reset = ResetSnapshot {
+            path: snapshot.clone(),
+            settings: serde_json::to_value(sample_settings()).expect("settings json"),
+        };
+        ensure_nothing_since_reset(&conn, &reset, &sample_settings()).expect("untouched");
+        let changed = AppSettings {
+            focus_min: 50,
+            ..sample_settings()
+        };
+        assert!(ensure_nothing_since_reset(&conn, &reset, &changed).is_err());
+        conn.execute(
+            "INSERT INTO tags (name, created_at) VALUES ('after reset', 0)",
+            [],
+        )
+        .expect("tag");
+        assert!(ensure_nothing_since_reset(&conn, &reset, &sample_settings()).is_err());
+        conn.execute_batch("DELETE FROM tags;").expect("drop tag");
+
+        restore_snapshot(&conn, &snapshot).expect("undo");
+        let sessions = fetch_sessions(&conn, &AnalyticsRange::default()).expect("fetch");
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].project_id, Some(1));
+        assert_eq!(
+            search_sessions(&conn, "snapshot", &AnalyticsRange::default())
+                .expect("search")
+                .len(),
+            1
+        );
+        let (timer, _, _) = load_or_create_timer(&conn, &sample_settings()).expect("timer");
+        assert_eq!(timer.current_project_id, Some(1));
+        conn.execute(
+            "INSERT INTO projects (name, created_at) VALUES ('Next', 0)",
+            [],
+        )
+        .expect("insert after restore");
+        assert_eq!(conn.last_insert_rowid(), 2);
 
-            app.manage(AppState {
-                model: Mutex::new(AppModel {
-                    conn,
-                    settings,
-                    timer,
-                }),
-                remote: Mutex::new(RemoteControlState { server: None }),
-            });
+        drop(conn);
+        fs::remove_dir_all(&dir).expect("cleanup");
+    }
 
-            setup_tray(app.handle())?;
+    #[test]
+    fn focus_ratings_are_grouped_and_correlated() {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+        conn.execute(
+            "INSERT INTO projects (name, created_at) VALUES ('Work', 0)",
+            [],
+        )
+        .expect("seed");
+
+        let mut timer = TimerState::default_with_settings(&sample_settings());
+        let mut ids = Vec::new();
+        for (interruptions, project_id) in [(0, Some(1)), (1, Some(1)), (4, None)] {
+            timer.interruptions = interruptions;
+            timer.current_project_id = project_id;
+            ids.push(
+                record_session(&conn, &timer, true, 10_000 + interruptions * 2_000)
+                    .expect("record")
+                    .id,
+            );
+        }
+        timer.phase = TimerPhase::ShortBreak;
+        timer.counts_as_focus = false;
+        let short_break = record_session(&conn, &timer, true, 30_000).expect("record");
+
+        rate_session(&conn, ids[0], Some(5), Some(4)).expect("rate");
+        rate_session(&conn, ids[1], Some(4), None).expect("rate");
+        let rated = rate_session(&conn, ids[2], Some(1), Some(2)).expect("rate");
+        assert_eq!(rated.focus_rating, Some(1));
+        assert!(rate_session(&conn, ids[0], Some(6), None).is_err());
+        assert!(rate_session(&conn, short_break.id, Some(3), None).is_err());
+
+        let stats = rating_correlations(&conn, &AnalyticsRange::default()).expect("ratings");
+        assert_eq!(stats.rated_sessions, 3);
+        let projects: Vec<_> = stats
+            .by_project
+            .iter()
+            .map(|bucket| {
+                (
+                    bucket.label.as_str(),
+                    bucket.sessions,
+                    bucket.avg_focus_rating,
+                )
+            })
+            .collect();
+        assert_eq!(projects, vec![("Work", 2, 4.5), ("No project", 1, 1.0)]);
+        assert_eq!(stats.by_project[0].avg_energy_level, Some(4.0));
+        let interruptions: Vec<_> = stats
+            .by_interruptions
+            .iter()
+            .map(|bucket| bucket.key.as_str())
+            .collect();
+        assert_eq!(interruptions, vec!["0", "1", "3+"]);
+        assert!(stats.interruptions_correlation.expect("correlation") < -0.9);
+        assert_eq!(stats.length_correlation, None);
+    }
 
-            {
-                let state = app.state::<AppState>();
-                let model = state.model.lock().map_err(|e| e.to_string())?;
-                update_tray_title(app.handle(), &model.timer);
-            }
+    #[test]
+    fn heatmap_splits_sessions_at_hour_boundaries() {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+
+        // Monday 09:50–10:30 with a 10:00–10:10 pause: 10 minutes at 9, 20 at 10.
+        let at = |hour, minute| {
+            Local
+                .with_ymd_and_hms(2024, 1, 1, hour, minute, 0)
+                .single()
+                .expect("local time")
+                .timestamp()
+        };
+        conn.execute(
+            "INSERT INTO sessions (started_at, ended_at, phase, duration_sec, completed, counts_as_focus)
+             VALUES (?1, ?2, 'focus', 1800, 1, 1)",
+            params![at(9, 50), at(10, 30)],
+        )
+        .expect("session");
+        conn.execute(
+            "INSERT INTO session_pauses (session_id, paused_at, resumed_at) VALUES (1, ?1, ?2)",
+            params![at(10, 0), at(10, 10)],
+        )
+        .expect("pause");
+
+        let cells = focus_heatmap(&conn, &AnalyticsRange::default(), 0).expect("heatmap");
+        assert_eq!(cells.len(), 7 * 24);
+        let cell = |weekday: u32, hour: u32| &cells[(weekday * 24 + hour) as usize];
+        assert_eq!(cell(0, 9).focus_seconds, 600);
+        assert_eq!(cell(0, 10).focus_seconds, 1200);
+        assert_eq!(cell(0, 10).completed_pomodoros, 1);
+        assert_eq!(cell(0, 9).completed_pomodoros, 0);
+        let total: i64 = cells.iter().map(|cell| cell.focus_seconds).sum();
+        assert_eq!(total, 1800);
+    }
 
-            spawn_timer_worker(app.handle().clone());
+    #[test]
+    fn heatmap_and_day_plans_use_the_recorded_offset_and_day_start() {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+
+        // Monday 2024-01-01 00:30–01:00 in Tokyo, before a 04:00 day start: Sunday's work.
+        let tokyo = FixedOffset::east_opt(9 * 3600).expect("offset");
+        let at = |minute| {
+            tokyo
+                .with_ymd_and_hms(2024, 1, 1, 0, minute, 0)
+                .single()
+                .expect("tokyo time")
+                .timestamp()
+        };
+        conn.execute(
+            "INSERT INTO sessions (started_at, ended_at, phase, duration_sec, completed, counts_as_focus, utc_offset_sec)
+             VALUES (?1, ?2, 'focus', 1800, 1, 1, ?3)",
+            params![at(30), at(30) + 1800, 9 * 3600],
+        )
+        .expect("session");
+
+        let cells = focus_heatmap(&conn, &AnalyticsRange::default(), 240).expect("heatmap");
+        let cell = |weekday: u32, hour: u32| &cells[(weekday * 24 + hour) as usize];
+        assert_eq!(cell(6, 0).focus_seconds, 1800);
+        assert_eq!(cell(6, 0).completed_pomodoros, 1);
+
+        let report = day_plan_report_for(&conn, "2023-12-31", 240).expect("report");
+        assert_eq!(report.focus_seconds, 1800);
+        assert_eq!(report.focus_pomodoros, 1);
+        let report = day_plan_report_for(&conn, "2024-01-01", 240).expect("report");
+        assert_eq!(report.focus_seconds, 0);
+    }
 
-            // Remote control server (optional; disabled by default).
-            {
-                let state = app.state::<AppState>();
-                let model = state.model.lock().map_err(|e| e.to_string())?;
-                if let Err(error) = remote_apply(app.handle(), &model.settings) {
-                    eprintln!("remote control startup warning: {error}");
-                }
-            }
-            Ok(())
-        })
-        .invoke_handler(tauri::generate_handler![
-            timer_start,
-            timer_pause,
-            timer_resume,
-            timer_skip,
-            timer_get_state,
-            timer_set_context,
-            session_complete,
-            analytics_get_summary,
-            analytics_get_timeseries,
-            projects_list,
-            projects_upsert,
-            tags_list,
-            tags_upsert,
-            export_csv,
-            export_json,
-            settings_get,
-            settings_update,
-            reset_all_data,
-            session_history,
-            get_local_ip,
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
+    #[test]
+    fn breakdown_groups_by_project_and_tag_with_fallback_buckets() {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+        conn.execute_batch(
+            "INSERT INTO projects (name, created_at) VALUES ('Work', 0);
+             INSERT INTO tags (name, created_at) VALUES ('deep', 0), ('admin', 0);
+             INSERT INTO sessions (started_at, ended_at, phase, duration_sec, completed, interruptions, project_id, counts_as_focus)
+             VALUES (0, 1500, 'focus', 1500, 1, 1, 1, 1),
+                    (2000, 2600, 'focus', 600, 0, 2, 1, 1),
+                    (3000, 3900, 'focus', 900, 1, 0, NULL, 1),
+                    (4000, 4300, 'short_break', 300, 1, 0, 1, 0);
+             INSERT INTO session_tags (session_id, tag_id) VALUES (1, 1), (1, 2), (2, 2);",
+        )
+        .expect("seed");
+        let range = AnalyticsRange::default();
+
+        let projects =
+            focus_breakdown(&conn, &range, BreakdownDimension::Project).expect("projects");
+        assert_eq!(projects.len(), 2);
+        assert_eq!(
+            (projects[0].id, projects[0].label.as_str()),
+            (Some(1), "Work")
+        );
+        assert_eq!(projects[0].focus_seconds, 2100);
+        assert_eq!(projects[0].interruptions, 3);
+        assert_eq!(projects[0].completion_rate, 0.5);
+        assert_eq!(projects[0].focus_percent, 70.0);
+        assert_eq!(
+            (projects[1].id, projects[1].label.as_str()),
+            (None, "No project")
+        );
+        assert_eq!(projects[1].pomodoro_percent, 50.0);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let tags = focus_breakdown(&conn, &range, BreakdownDimension::Tag).expect("tags");
+        let rows: Vec<_> = tags
+            .iter()
+            .map(|row| (row.label.as_str(), row.focus_seconds, row.sessions))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![("admin", 2100, 2), ("deep", 1500, 1), ("Untagged", 900, 1)]
+        );
 
-    fn sample_settings() -> AppSettings {
-        AppSettings {
-            focus_min: 25,
-            short_break_min: 5,
-            long_break_min: 15,
-            long_break_every: 4,
-            theme: "light".to_string(),
-            sound_enabled: true,
-            notifications_enabled: true,
-            remote_control_enabled: false,
-            remote_control_port: 48484,
-            remote_control_token: "testtoken".to_string(),
+        // Subproject rows are labelled with their full path.
+        conn.execute_batch(
+            "INSERT INTO projects (name, created_at) VALUES ('Acme', 0), ('Globex', 0);
+             INSERT INTO projects (name, created_at, parent_id) VALUES ('Website', 0, 2), ('Shop', 0, 3);
+             INSERT INTO sessions (started_at, ended_at, phase, duration_sec, completed, interruptions, project_id, counts_as_focus)
+             VALUES (5000, 6500, 'focus', 1500, 1, 0, 4, 1),
+                    (7000, 7600, 'focus', 600, 1, 0, 5, 1);",
+        )
+        .expect("subprojects");
+        let projects =
+            focus_breakdown(&conn, &range, BreakdownDimension::Project).expect("projects");
+        let labels: Vec<_> = projects.iter().map(|row| row.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            vec!["Work", "Acme / Website", "No project", "Globex / Shop"]
+        );
+    }
+
+    #[test]
+    fn timeseries_buckets_by_granularity_and_zero_fills() {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+        let noon = |month, day| {
+            Local
+                .with_ymd_and_hms(2024, month, day, 12, 0, 0)
+                .single()
+                .expect("local time")
+                .timestamp()
+        };
+        for ended_at in [noon(1, 2), noon(1, 3), noon(1, 17), noon(3, 1)] {
+            conn.execute(
+                "INSERT INTO sessions (started_at, ended_at, phase, duration_sec, completed, counts_as_focus)
+                 VALUES (?1, ?2, 'focus', 1500, 1, 1)",
+                params![ended_at - 1500, ended_at],
+            )
+            .expect("session");
         }
+        let range = AnalyticsRange {
+            from: Some(noon(1, 1)),
+            to: Some(noon(3, 31)),
+            ..Default::default()
+        };
+        let series = |granularity, zero_fill| -> Vec<(String, i64)> {
+            focus_timeseries(&conn, &range, granularity, zero_fill, 0)
+                .expect("series")
+                .into_iter()
+                .map(|point| (point.date, point.completed_pomodoros))
+                .collect()
+        };
+
+        let weeks = series(TimeseriesGranularity::Week, false);
+        assert_eq!(
+            weeks,
+            vec![
+                ("2024-01-01".to_string(), 2),
+                ("2024-01-15".to_string(), 1),
+                ("2024-02-26".to_string(), 1)
+            ]
+        );
+        let filled = series(TimeseriesGranularity::Week, true);
+        assert_eq!(filled.len(), 13);
+        assert_eq!(filled[1], ("2024-01-08".to_string(), 0));
+        let months = series(TimeseriesGranularity::Month, true);
+        assert_eq!(
+            months,
+            vec![
+                ("2024-01-01".to_string(), 3),
+                ("2024-02-01".to_string(), 0),
+                ("2024-03-01".to_string(), 1)
+            ]
+        );
+        assert_eq!(
+            series(TimeseriesGranularity::Year, true),
+            vec![("2024-01-01".to_string(), 4)]
+        );
+        assert_eq!(series(TimeseriesGranularity::Day, true).len(), 91);
     }
 
     #[test]
-    fn advances_to_long_break_every_fourth_cycle() {
-        let settings = sample_settings();
-        let mut timer = TimerState::default_with_settings(&settings);
+    fn pause_intervals_are_linked_to_the_recorded_session() {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+
+        let mut timer = TimerState::default_with_settings(&sample_settings());
+        begin_running(&mut timer);
+        pause_timer(&mut timer, None);
+        begin_running(&mut timer);
+        pause_timer(&mut timer, None);
+        assert_eq!(timer.interruptions, 2);
+        assert_eq!(timer.pauses.len(), 2);
+        assert!(timer.pauses[0].resumed_at.is_some());
+        assert_eq!(timer.pauses[1].resumed_at, None);
+
+        let recorded = record_session(&conn, &timer, false, now_ts()).expect("record");
+        let sessions = fetch_sessions(&conn, &AnalyticsRange::default()).expect("fetch");
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, recorded.id);
+        assert_eq!(sessions[0].pauses, timer.pauses);
+
+        advance_timer(&mut timer, &sample_settings(), None);
+        assert!(timer.pauses.is_empty());
+    }
 
-        timer.phase = TimerPhase::Focus;
-        timer.cycle_index = 3;
-        advance_timer(&mut timer, &settings);
+    #[test]
+    fn interruption_reasons_are_logged_and_broken_down() {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+
+        let mut timer = TimerState::default_with_settings(&sample_settings());
+        begin_running(&mut timer);
+        pause_timer(
+            &mut timer,
+            Some(InterruptionReason {
+                kind: Some(InterruptionKind::External),
+                note: Some("  Slack ".to_string()),
+            }),
+        );
+        begin_running(&mut timer);
+        timer.interruption_log.push(InterruptionEntry {
+            occurred_at: now_ts(),
+            kind: Some(InterruptionKind::External),
+            note: Some("slack".to_string()),
+            paused: false,
+        });
+        pause_timer(&mut timer, None);
+        record_session(&conn, &timer, false, now_ts()).expect("record");
+
+        let sessions = fetch_sessions(&conn, &AnalyticsRange::default()).expect("fetch");
+        assert_eq!(sessions[0].interruption_log.len(), 3);
+        assert_eq!(
+            sessions[0].interruption_log[0].note.as_deref(),
+            Some("Slack")
+        );
 
-        assert_eq!(timer.phase, TimerPhase::LongBreak);
-        assert_eq!(timer.cycle_index, 4);
+        let stats = interruption_breakdown(&conn, &AnalyticsRange::default()).expect("stats");
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].kind, Some(InterruptionKind::External));
+        assert_eq!(stats[0].note.as_deref(), Some("slack"));
+        assert_eq!((stats[0].pauses, stats[0].distractions), (1, 1));
+        assert_eq!((stats[1].kind.clone(), stats[1].pauses), (None, 1));
     }
 
     #[test]
@@ -2104,6 +8293,18 @@ mod tests {
                 interruptions: 0,
                 project_id: None,
                 tag_ids: vec![],
+                counts_as_focus: true,
+                profile_id: None,
+                planned_duration_sec: 1500,
+                overtime_sec: 0,
+                adjusted_sec: 0,
+                pauses: vec![],
+                interruption_log: vec![],
+                note: None,
+                task_id: None,
+                focus_rating: None,
+                energy_level: None,
+                utc_offset_sec: None,
             },
             SessionRecord {
                 id: 2,
@@ -2115,10 +8316,338 @@ mod tests {
                 interruptions: 0,
                 project_id: None,
                 tag_ids: vec![],
+                counts_as_focus: true,
+                profile_id: None,
+                planned_duration_sec: 1500,
+                overtime_sec: 0,
+                adjusted_sec: 0,
+                pauses: vec![],
+                interruption_log: vec![],
+                note: None,
+                task_id: None,
+                focus_rating: None,
+                energy_level: None,
+                utc_offset_sec: None,
             },
         ];
 
-        assert!(calculate_streak_days(&sessions) >= 2);
+        let today = analytics_day(now, None, 0);
+        assert!(calculate_streaks(&sessions, &AppSettings::default(), today).current_days >= 2);
+    }
+
+    #[test]
+    fn streaks_respect_goal_rest_days_and_vacations() {
+        // 2024-01-01 is a Monday.
+        let date = |day| NaiveDate::from_ymd_opt(2024, 1, day).expect("date");
+        let noon = |day: u32| {
+            Utc.with_ymd_and_hms(2024, 1, day, 12, 0, 0)
+                .single()
+                .expect("utc time")
+                .timestamp()
+        };
+        let mut sessions = Vec::new();
+        // Mon–Fri of the first week, two pomodoros each except one on Wednesday,
+        // then Mon 8th–Tue 9th and Thu 11th.
+        for (day, count) in [
+            (1, 2),
+            (2, 2),
+            (3, 1),
+            (4, 2),
+            (5, 2),
+            (8, 2),
+            (9, 2),
+            (11, 2),
+        ] {
+            for index in 0..count {
+                let mut session =
+                    focus_session(sessions.len() as i64, noon(day) - 1500, noon(day) + index);
+                session.utc_offset_sec = Some(0);
+                sessions.push(session);
+            }
+        }
+
+        let plain = calculate_streaks(&sessions, &AppSettings::default(), date(11));
+        assert_eq!((plain.current_days, plain.longest_days), (1, 5));
+        assert_eq!(plain.longest_start.as_deref(), Some("2024-01-01"));
+        assert!(plain.today_goal_met);
+
+        let goal = AppSettings {
+            daily_goal_pomodoros: 2,
+            rest_weekdays: vec![5, 6],
+            vacation_dates: vec!["2024-01-10".to_string()],
+            ..AppSettings::default()
+        };
+        let stats = calculate_streaks(&sessions, &goal, date(11));
+        // Wednesday the 3rd misses the goal; the weekend and the 10th are excused.
+        assert_eq!(stats.current_days, 5);
+        assert_eq!(stats.longest_days, 5);
+        assert_eq!(stats.longest_start.as_deref(), Some("2024-01-04"));
+        assert_eq!(stats.longest_end.as_deref(), Some("2024-01-11"));
+
+        // An open today does not break the streak; a missed yesterday does.
+        assert_eq!(
+            calculate_streaks(&sessions, &goal, date(12)).current_days,
+            5
+        );
+        assert_eq!(
+            calculate_streaks(&sessions, &goal, date(13)).current_days,
+            0
+        );
+        assert_eq!(
+            calculate_streaks(&sessions, &AppSettings::default(), date(13)).current_days,
+            0
+        );
+    }
+
+    #[test]
+    fn streaks_look_past_the_analytics_window() {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+        let noon = |day: u32| {
+            Utc.with_ymd_and_hms(2024, 1, day, 12, 0, 0)
+                .single()
+                .expect("utc time")
+                .timestamp()
+        };
+        // One pomodoro a day from the 1st through the 10th.
+        for day in 1..=10 {
+            conn.execute(
+                "INSERT INTO sessions (started_at, ended_at, phase, duration_sec, completed, counts_as_focus, utc_offset_sec)
+                 VALUES (?1, ?2, 'focus', 1500, 1, 1, 0)",
+                params![noon(day) - 1500, noon(day)],
+            )
+            .expect("session");
+        }
+
+        let last_two_days = AnalyticsRange {
+            from: Some(noon(9) - 12 * 3600),
+            to: Some(noon(10) + 12 * 3600),
+            ..AnalyticsRange::default()
+        };
+        let today = NaiveDate::from_ymd_opt(2024, 1, 10).expect("date");
+        let stats =
+            streaks_for(&conn, &last_two_days, &AppSettings::default(), today).expect("streaks");
+        assert_eq!((stats.current_days, stats.longest_days), (10, 10));
+        assert_eq!(stats.longest_start.as_deref(), Some("2024-01-01"));
+
+        let other_project = AnalyticsRange {
+            project_id: Some(1),
+            ..last_two_days
+        };
+        let stats =
+            streaks_for(&conn, &other_project, &AppSettings::default(), today).expect("streaks");
+        assert_eq!(stats.longest_days, 0);
+    }
+
+    #[test]
+    fn analytics_days_follow_day_start_and_recorded_offset() {
+        // 2024-03-10 01:30 UTC.
+        let late = Utc
+            .with_ymd_and_hms(2024, 3, 10, 1, 30, 0)
+            .single()
+            .expect("utc time")
+            .timestamp();
+        let date = |day| NaiveDate::from_ymd_opt(2024, 3, day).expect("date");
+
+        assert_eq!(analytics_day(late, Some(0), 0), date(10));
+        assert_eq!(analytics_day(late, Some(0), 4 * 60), date(9));
+        // Recorded in UTC-5, where it was still the evening of the 9th.
+        assert_eq!(analytics_day(late, Some(-5 * 3600), 0), date(9));
+        assert_eq!(analytics_day(late, Some(9 * 3600), 4 * 60), date(10));
+
+        assert_eq!(parse_day_start("04:30"), Some(270));
+        assert_eq!(parse_day_start("24:00"), None);
+        let settings = normalize_settings(AppSettings {
+            day_starts_at: "7:5".to_string(),
+            ..AppSettings::default()
+        });
+        assert_eq!(settings.day_starts_at, "07:05");
+
+        let mut session = focus_session(1, late - 1500, late);
+        session.utc_offset_sec = Some(0);
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+        let timer = TimerState::default_with_settings(&sample_settings());
+        let recorded = record_session(&conn, &timer, false, late).expect("record");
+        assert_eq!(recorded.utc_offset_sec, Some(local_utc_offset(late)));
+        assert_eq!(
+            fetch_session(&conn, recorded.id)
+                .expect("fetch")
+                .expect("session")
+                .utc_offset_sec,
+            recorded.utc_offset_sec
+        );
+        assert_eq!(session_day(&session, 4 * 60), date(9));
+    }
+
+    fn sample_plan() -> CyclePlan {
+        CyclePlan {
+            id: 7,
+            name: "Deep work".to_string(),
+            steps: normalize_plan_steps(vec![
+                CyclePlanStep {
+                    phase: TimerPhase::Focus,
+                    label: String::new(),
+                    duration_min: 50,
+                    counts_as_focus: false,
+                },
+                CyclePlanStep {
+                    phase: TimerPhase::ShortBreak,
+                    label: "Break".to_string(),
+                    duration_min: 10,
+                    counts_as_focus: true,
+                },
+                CyclePlanStep {
+                    phase: TimerPhase::Custom("review".to_string()),
+                    label: "Review".to_string(),
+                    duration_min: 15,
+                    counts_as_focus: true,
+                },
+            ])
+            .expect("plan steps should be valid"),
+        }
+    }
+
+    #[test]
+    fn advances_through_cycle_plan_steps_and_wraps() {
+        let settings = sample_settings();
+        let plan = sample_plan();
+        let mut timer = TimerState::default_with_settings(&settings);
+        timer.plan_id = Some(plan.id);
+        let timer = normalize_timer_state(timer, &settings, Some(&plan));
+        assert_eq!(timer.phase_total_seconds, 50 * 60);
+        assert!(timer.counts_as_focus);
+
+        let mut timer = timer;
+        advance_timer(&mut timer, &settings, Some(&plan));
+        assert_eq!(timer.phase, TimerPhase::ShortBreak);
+        assert!(!timer.counts_as_focus);
+        assert_eq!(timer.cycle_index, 1);
+
+        advance_timer(&mut timer, &settings, Some(&plan));
+        assert_eq!(timer.phase, TimerPhase::Custom("review".to_string()));
+        assert_eq!(timer.phase_label, "Review");
+        assert_eq!(timer.remaining_seconds, 15 * 60);
+        assert!(timer.counts_as_focus);
+
+        advance_timer(&mut timer, &settings, Some(&plan));
+        assert_eq!(timer.phase, TimerPhase::Focus);
+        assert_eq!(timer.plan_step, 0);
+        assert_eq!(timer.cycle_index, 2);
+    }
+
+    #[test]
+    fn falls_back_to_builtin_cycle_when_plan_is_missing() {
+        let settings = sample_settings();
+        let mut timer = TimerState::default_with_settings(&settings);
+        timer.plan_id = Some(42);
+        timer.plan_step = 2;
+        timer.phase = TimerPhase::Custom("review".to_string());
+
+        let timer = normalize_timer_state(timer, &settings, None);
+        assert_eq!(timer.plan_id, None);
+        assert_eq!(timer.phase, TimerPhase::Focus);
+        assert_eq!(timer.remaining_seconds, 25 * 60);
+    }
+
+    #[test]
+    fn shortened_plan_keeps_the_plan_and_wraps_the_step() {
+        let settings = sample_settings();
+        let plan = sample_plan();
+        let mut timer = TimerState::default_with_settings(&settings);
+        timer.plan_id = Some(plan.id);
+        timer.plan_step = 4;
+        timer.started_at = Some(now_ts() - 60);
+
+        let timer = normalize_timer_state(timer, &settings, Some(&plan));
+        assert_eq!(timer.plan_id, Some(plan.id));
+        assert_eq!(timer.plan_step, 1);
+        assert_eq!(timer.phase, TimerPhase::ShortBreak);
+        assert_eq!(timer.started_at, None);
+        assert_eq!(timer.remaining_seconds, 10 * 60);
+    }
+
+    #[test]
+    fn custom_phases_round_trip_as_plain_strings() {
+        let phase: TimerPhase = serde_json::from_str("\"review\"").expect("custom phase");
+        assert_eq!(phase, TimerPhase::Custom("review".to_string()));
+        assert_eq!(phase.to_string(), "Review");
+        assert_eq!(
+            serde_json::to_string(&TimerPhase::ShortBreak).expect("serialize"),
+            "\"short_break\""
+        );
+        assert!(serde_json::from_str::<TimerPhase>("\"Not A Kind\"").is_err());
+    }
+
+    #[test]
+    fn records_custom_focus_like_phases_with_project_and_tags() {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+        conn.execute("INSERT INTO tags (name, created_at) VALUES ('deep', 0)", [])
+            .expect("tag");
+        let tag_id = conn.last_insert_rowid();
+
+        let settings = sample_settings();
+        let plan = sample_plan();
+        let mut timer = TimerState::default_with_settings(&settings);
+        timer.plan_id = Some(plan.id);
+        timer.plan_step = 2;
+        timer.current_project_id = None;
+        timer.current_tag_ids = vec![tag_id];
+        let timer = normalize_timer_state(timer, &settings, Some(&plan));
+
+        let session = record_session(&conn, &timer, true, now_ts()).expect("record");
+        assert!(session.counts_as_focus);
+        assert_eq!(session.duration_sec, 15 * 60);
+
+        let sessions = fetch_sessions(
+            &conn,
+            &AnalyticsRange {
+                tag_id: Some(tag_id),
+                ..Default::default()
+            },
+        )
+        .expect("fetch");
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].phase, TimerPhase::Custom("review".to_string()));
+        assert!(sessions[0].counts_as_focus);
+    }
+
+    #[test]
+    fn active_profile_overrides_builtin_durations_and_is_recorded() {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        init_database(&conn).expect("schema");
+        conn.execute(
+            "INSERT INTO timer_profiles (name, focus_min, short_break_min, long_break_min, long_break_every, created_at)
+             VALUES ('Deep work', 50, 10, 20, 3, 0)",
+            [],
+        )
+        .expect("profile");
+        let profile = fetch_timer_profile(&conn, conn.last_insert_rowid())
+            .expect("fetch profile")
+            .expect("profile exists");
+
+        let settings = profile_settings(&sample_settings(), Some(&profile));
+        let mut timer = TimerState::default_with_settings(&sample_settings());
+        timer.profile_id = Some(profile.id);
+        let mut timer = normalize_timer_state(timer, &settings, None);
+        assert_eq!(timer.phase_total_seconds, 50 * 60);
+
+        let session = record_session(&conn, &timer, true, now_ts()).expect("record");
+        assert_eq!(session.profile_id, Some(profile.id));
+
+        advance_timer(&mut timer, &settings, None);
+        assert_eq!(timer.phase_total_seconds, 10 * 60);
+
+        let by_profile = fetch_sessions(
+            &conn,
+            &AnalyticsRange {
+                profile_id: Some(profile.id),
+                ..Default::default()
+            },
+        )
+        .expect("fetch");
+        assert_eq!(by_profile.len(), 1);
     }
 
     #[test]
@@ -19,6 +19,7 @@ import {
   settingsGet,
   settingsUpdate,
   resetAllData,
+  sessionRate,
   tagsList,
   tagsUpsert,
   timerGetState,
@@ -27,12 +28,14 @@ import {
   timerSkip,
   timerStart,
   timerSetContext,
+  timerTakeRecovered,
 } from "./api";
 import type {
   AnalyticsRange,
   AppSettings,
   AppTheme,
   PhaseCompletedEvent,
+  PhaseRecoveredEvent,
   SessionRecord,
   TimerPhase,
   TimerState,
@@ -48,6 +51,7 @@ import SettingsPanel from "./components/SettingsPanel";
 import HistoryList from "./components/HistoryList";
 import TitleBar from "./components/TitleBar";
 import Sidebar from "./components/Sidebar";
+import FocusRatingPrompt from "./components/FocusRatingPrompt";
 
 import StatsChart from "./components/StatsChart";
 import { Input } from "@/components/ui/input";
@@ -108,6 +112,7 @@ export default function App() {
   const [newTagName, setNewTagName] = useState("");
   const [statusMessage, setStatusMessage] = useState("");
   const [actionBusy, setActionBusy] = useState(false);
+  const [rateSessionId, setRateSessionId] = useState<number | null>(null);
 
   // Navigation State
   const [activeTab, setActiveTab] = useState<"timer" | "stats" | "settings">("timer");
@@ -181,6 +186,16 @@ export default function App() {
       .catch((error) => {
         reportActionError("Failed to load timer state.", error);
       });
+    // A phase that ended while the app was closed is recovered before the window loads.
+    timerTakeRecovered()
+      .then((recovered) => {
+        if (recovered) {
+          showRecovered(recovered);
+        }
+      })
+      .catch((error) => {
+        reportActionError("Failed to load recovered phase.", error);
+      });
   }, []);
 
   // Update body attribute for phase theming
@@ -194,6 +209,7 @@ export default function App() {
     let unlistenState: (() => void) | undefined;
     let unlistenPhase: (() => void) | undefined;
     let unlistenSession: (() => void) | undefined;
+    let unlistenRecovered: (() => void) | undefined;
     // Window close is now handled by TitleBar, but we can keep listener if needed for other things?
     // Actually standard close behavior is fine since we use hide() in TitleBar.
 
@@ -222,6 +238,10 @@ export default function App() {
           if (settingsDraft?.soundEnabled) {
             playTone();
           }
+          // Breaks carry no session to rate; keep an unanswered prompt until it is dismissed.
+          if (event.payload.rateSessionId !== null) {
+            setRateSessionId(event.payload.rateSessionId);
+          }
 
           queryClient.invalidateQueries({ queryKey: ["summary"] });
           queryClient.invalidateQueries({ queryKey: ["series"] });
@@ -230,6 +250,10 @@ export default function App() {
         },
       );
 
+      unlistenRecovered = await listen<PhaseRecoveredEvent>("timer://recovered", (event) => {
+        showRecovered(event.payload);
+      });
+
       unlistenSession = await listen<SessionRecord>("session://completed", () => {
         queryClient.invalidateQueries({ queryKey: ["summary"] });
         queryClient.invalidateQueries({ queryKey: ["series"] });
@@ -246,6 +270,7 @@ export default function App() {
       unlistenState?.();
       unlistenPhase?.();
       unlistenSession?.();
+      unlistenRecovered?.();
     };
   }, [queryClient, settingsDraft?.notificationsEnabled, settingsDraft?.soundEnabled]);
 
@@ -261,6 +286,15 @@ export default function App() {
     ]);
   }
 
+  function showRecovered(event: PhaseRecoveredEvent) {
+    setStatusMessage(
+      `${event.completedLabel} finished while the app was away and was recorded.`,
+    );
+    if (event.rateSessionId !== null) {
+      setRateSessionId(event.rateSessionId);
+    }
+  }
+
   function reportActionError(title: string, error: unknown) {
     const details = toErrorMessage(error);
     setStatusMessage(details);
@@ -336,6 +370,24 @@ export default function App() {
     }
   }
 
+  async function onRateSession(focusRating: number, energyLevel: number | null) {
+    if (rateSessionId === null) {
+      return;
+    }
+
+    setActionBusy(true);
+    try {
+      await sessionRate(rateSessionId, focusRating, energyLevel);
+      setRateSessionId(null);
+      setStatusMessage("Focus rating saved.");
+      queryClient.invalidateQueries({ queryKey: ["history"] });
+    } catch (error) {
+      reportActionError("Failed to save rating.", error);
+    } finally {
+      setActionBusy(false);
+    }
+  }
+
   async function onSaveSettings() {
     if (!settingsDraft) {
       return;
@@ -519,6 +571,15 @@ export default function App() {
                     busy={actionBusy}
                   />
 
+                  {rateSessionId !== null && (
+                    <FocusRatingPrompt
+                      key={rateSessionId}
+                      onSubmit={onRateSession}
+                      onDismiss={() => setRateSessionId(null)}
+                      busy={actionBusy}
+                    />
+                  )}
+
                   {/* Task Selection */}
                   <div className="grid gap-4 sm:grid-cols-2">
                     <label className="flex flex-col gap-2">
@@ -6,25 +6,52 @@ import type {
   AnalyticsSummary,
   AppSettings,
   AppSettingsPatch,
+  BreakdownDimension,
+  BreakdownRow,
+  CompleteSessionRequest,
+  CyclePlan,
+  CyclePlanInput,
+  DayPlanItem,
+  DayPlanItemInput,
+  DayPlanReport,
   ExportRange,
   ExportResult,
+  HeatmapCell,
+  InterruptionReason,
+  InterruptionReasonStat,
+  PhaseRecoveredEvent,
   Project,
   ProjectInput,
+  RatingCorrelations,
   ResetAllResult,
   SessionRecord,
   StartTimerRequest,
+  StreakStats,
   Tag,
   TagInput,
+  TagNamespaceStat,
+  Task,
+  TaskInput,
+  TimerProfile,
+  TimerProfileInput,
   TimerState,
+  TimeseriesOptions,
   TimeseriesPoint,
+  TrashItem,
+  TrashKind,
+  UpdateSessionRequest,
 } from "./types";
 
 export async function timerStart(payload?: StartTimerRequest) {
   return invoke<TimerState>("timer_start", { payload });
 }
 
-export async function timerPause() {
-  return invoke<TimerState>("timer_pause");
+export async function timerPause(reason?: InterruptionReason) {
+  return invoke<TimerState>("timer_pause", { reason });
+}
+
+export async function timerNoteDistraction(reason: InterruptionReason) {
+  return invoke<TimerState>("timer_note_distraction", { reason });
 }
 
 export async function timerResume(payload?: StartTimerRequest) {
@@ -35,10 +62,22 @@ export async function timerSkip() {
   return invoke<TimerState>("timer_skip");
 }
 
+export async function timerResetPhase() {
+  return invoke<TimerState>("timer_reset_phase");
+}
+
+export async function timerAdjust(deltaSeconds: number) {
+  return invoke<TimerState>("timer_adjust", { deltaSeconds });
+}
+
 export async function timerGetState() {
   return invoke<TimerState>("timer_get_state");
 }
 
+export async function timerTakeRecovered() {
+  return invoke<PhaseRecoveredEvent | null>("timer_take_recovered");
+}
+
 export async function timerSetContext(payload: StartTimerRequest) {
   return invoke<TimerState>("timer_set_context", { payload });
 }
@@ -47,14 +86,66 @@ export async function analyticsGetSummary(range: AnalyticsRange) {
   return invoke<AnalyticsSummary>("analytics_get_summary", { range });
 }
 
-export async function analyticsGetTimeseries(range: AnalyticsRange) {
-  return invoke<TimeseriesPoint[]>("analytics_get_timeseries", { range });
+export async function analyticsGetTimeseries(range: AnalyticsRange, options: TimeseriesOptions = {}) {
+  return invoke<TimeseriesPoint[]>("analytics_get_timeseries", {
+    range,
+    granularity: options.granularity ?? null,
+    zeroFill: options.zeroFill ?? null,
+  });
+}
+
+export async function analyticsGetInterruptions(range: AnalyticsRange) {
+  return invoke<InterruptionReasonStat[]>("analytics_get_interruptions", { range });
+}
+
+export async function analyticsGetStreaks(range: AnalyticsRange) {
+  return invoke<StreakStats>("analytics_get_streaks", { range });
+}
+
+export async function analyticsGetBreakdown(range: AnalyticsRange, dimension: BreakdownDimension) {
+  return invoke<BreakdownRow[]>("analytics_get_breakdown", { range, dimension });
+}
+
+export async function analyticsGetHeatmap(range: AnalyticsRange) {
+  return invoke<HeatmapCell[]>("analytics_get_heatmap", { range });
+}
+
+export async function analyticsGetRatings(range: AnalyticsRange) {
+  return invoke<RatingCorrelations>("analytics_get_ratings", { range });
+}
+
+export async function analyticsGetTagNamespace(range: AnalyticsRange, tagId: number) {
+  return invoke<TagNamespaceStat[]>("analytics_get_tag_namespace", { range, tagId });
 }
 
 export async function sessionHistory(range: AnalyticsRange) {
   return invoke<SessionRecord[]>("session_history", { range });
 }
 
+export async function sessionSearch(query: string, range: AnalyticsRange) {
+  return invoke<SessionRecord[]>("session_search", { query, range });
+}
+
+export async function sessionComplete(payload: CompleteSessionRequest) {
+  return invoke<SessionRecord>("session_complete", { payload });
+}
+
+export async function sessionUpdate(payload: UpdateSessionRequest) {
+  return invoke<SessionRecord>("session_update", { payload });
+}
+
+export async function sessionDelete(sessionIds: number[]) {
+  return invoke<number>("session_delete", { sessionIds });
+}
+
+export async function sessionRate(
+  sessionId: number,
+  focusRating: number | null,
+  energyLevel: number | null,
+) {
+  return invoke<SessionRecord>("session_rate", { sessionId, focusRating, energyLevel });
+}
+
 export async function projectsList() {
   return invoke<Project[]>("projects_list");
 }
@@ -71,6 +162,82 @@ export async function tagsUpsert(input: TagInput) {
   return invoke<Tag>("tags_upsert", { input });
 }
 
+export async function projectsDelete(projectId: number, reassignTo?: number | null) {
+  return invoke<void>("projects_delete", { projectId, reassignTo: reassignTo ?? null });
+}
+
+export async function projectsMerge(sourceId: number, targetId: number) {
+  return invoke<void>("projects_merge", { sourceId, targetId });
+}
+
+export async function tagsDelete(tagId: number, reassignTo?: number | null) {
+  return invoke<void>("tags_delete", { tagId, reassignTo: reassignTo ?? null });
+}
+
+export async function tagsMerge(sourceId: number, targetId: number) {
+  return invoke<void>("tags_merge", { sourceId, targetId });
+}
+
+export async function tasksList(projectId?: number | null, includeDone = false) {
+  return invoke<Task[]>("tasks_list", { projectId: projectId ?? null, includeDone });
+}
+
+export async function tasksUpsert(input: TaskInput) {
+  return invoke<Task>("tasks_upsert", { input });
+}
+
+export async function tasksReorder(taskIds: number[]) {
+  return invoke<void>("tasks_reorder", { taskIds });
+}
+
+export async function tasksDelete(taskId: number) {
+  return invoke<void>("tasks_delete", { taskId });
+}
+
+export async function dayPlanGet(date?: string) {
+  return invoke<DayPlanItem[]>("day_plan_get", { date: date ?? null });
+}
+
+export async function dayPlanSet(items: DayPlanItemInput[], date?: string) {
+  return invoke<DayPlanItem[]>("day_plan_set", { date: date ?? null, items });
+}
+
+export async function dayPlanReport(date?: string) {
+  return invoke<DayPlanReport>("day_plan_report", { date: date ?? null });
+}
+
+export async function cyclePlansList() {
+  return invoke<CyclePlan[]>("cycle_plans_list");
+}
+
+export async function cyclePlansUpsert(input: CyclePlanInput) {
+  return invoke<CyclePlan>("cycle_plans_upsert", { input });
+}
+
+export async function cyclePlansDelete(planId: number) {
+  return invoke<void>("cycle_plans_delete", { planId });
+}
+
+export async function timerSetPlan(planId: number | null) {
+  return invoke<TimerState>("timer_set_plan", { planId });
+}
+
+export async function timerProfilesList() {
+  return invoke<TimerProfile[]>("timer_profiles_list");
+}
+
+export async function timerProfilesUpsert(input: TimerProfileInput) {
+  return invoke<TimerProfile>("timer_profiles_upsert", { input });
+}
+
+export async function timerProfilesDelete(profileId: number) {
+  return invoke<void>("timer_profiles_delete", { profileId });
+}
+
+export async function timerSetProfile(profileId: number | null) {
+  return invoke<TimerState>("timer_set_profile", { profileId });
+}
+
 export async function settingsGet() {
   return invoke<AppSettings>("settings_get");
 }
@@ -83,6 +250,22 @@ export async function resetAllData() {
   return invoke<ResetAllResult>("reset_all_data");
 }
 
+export async function resetUndo() {
+  return invoke<ResetAllResult>("reset_undo");
+}
+
+export async function trashList() {
+  return invoke<TrashItem[]>("trash_list");
+}
+
+export async function trashRestore(kind: TrashKind, id: number) {
+  return invoke<void>("trash_restore", { kind, id });
+}
+
+export async function trashPurge(kind?: TrashKind, id?: number) {
+  return invoke<number>("trash_purge", { kind: kind ?? null, id: id ?? null });
+}
+
 async function writeExport(
   command: "export_csv" | "export_json",
   range: ExportRange,
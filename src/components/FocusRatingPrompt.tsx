@@ -0,0 +1,58 @@
+import { useState } from "react";
+import { Button } from "@/components/ui/button";
+
+interface FocusRatingPromptProps {
+  onSubmit: (focusRating: number, energyLevel: number | null) => void;
+  onDismiss: () => void;
+  busy: boolean;
+}
+
+const SCALE = [1, 2, 3, 4, 5];
+
+export default function FocusRatingPrompt({ onSubmit, onDismiss, busy }: FocusRatingPromptProps) {
+  const [focusRating, setFocusRating] = useState<number | null>(null);
+  const [energyLevel, setEnergyLevel] = useState<number | null>(null);
+
+  return (
+    <div className="rounded-xl border bg-card p-4 text-card-foreground shadow-sm space-y-3">
+      <h3 className="text-sm font-semibold">How focused were you?</h3>
+      <div className="flex gap-2">
+        {SCALE.map((value) => (
+          <Button
+            key={value}
+            size="sm"
+            variant={focusRating === value ? "default" : "outline"}
+            onClick={() => setFocusRating(value)}
+          >
+            {value}
+          </Button>
+        ))}
+      </div>
+      <h3 className="text-sm font-semibold">Energy level (optional)</h3>
+      <div className="flex gap-2">
+        {SCALE.map((value) => (
+          <Button
+            key={value}
+            size="sm"
+            variant={energyLevel === value ? "default" : "outline"}
+            onClick={() => setEnergyLevel(energyLevel === value ? null : value)}
+          >
+            {value}
+          </Button>
+        ))}
+      </div>
+      <div className="flex justify-end gap-2">
+        <Button size="sm" variant="ghost" onClick={onDismiss}>
+          Skip
+        </Button>
+        <Button
+          size="sm"
+          disabled={focusRating === null || busy}
+          onClick={() => focusRating !== null && onSubmit(focusRating, energyLevel)}
+        >
+          Save rating
+        </Button>
+      </div>
+    </div>
+  );
+}
@@ -115,6 +115,28 @@ export default function SettingsPanel({ settings, onUpdate, onSave }: SettingsPa
                             onChange={(e) => handleChange("longBreakEvery", Number(e.target.value))}
                         />
                     </div>
+                    <div className="space-y-2">
+                        <label className="text-sm font-medium leading-none peer-disabled:cursor-not-allowed peer-disabled:opacity-70">
+                            Day Starts At
+                        </label>
+                        <Input
+                            type="time"
+                            value={settings.dayStartsAt}
+                            onChange={(e) => handleChange("dayStartsAt", e.target.value)}
+                        />
+                    </div>
+                    <div className="space-y-2">
+                        <label className="text-sm font-medium leading-none peer-disabled:cursor-not-allowed peer-disabled:opacity-70">
+                            Daily Goal (pomodoros)
+                        </label>
+                        <Input
+                            type="number"
+                            min={0}
+                            max={24}
+                            value={settings.dailyGoalPomodoros}
+                            onChange={(e) => handleChange("dailyGoalPomodoros", Number(e.target.value))}
+                        />
+                    </div>
                 </div>
 
                 <div className="space-y-4">
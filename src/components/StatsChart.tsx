@@ -22,7 +22,7 @@ export default function StatsChart({ period, onPeriodChange, timeseriesData, ses
             }));
 
             sessionData?.forEach(session => {
-                if (session.phase !== "focus") {
+                if (!session.countsAsFocus) {
                     return;
                 }
                 const hour = new Date(session.startedAt * 1000).getHours();
@@ -1,4 +1,5 @@
-export type TimerPhase = "focus" | "short_break" | "long_break";
+// Built-in phases, or the slug of a custom phase kind from a cycle plan.
+export type TimerPhase = "focus" | "short_break" | "long_break" | (string & {});
 export type AppTheme = "light" | "dark";
 
 export interface AppSettings {
@@ -12,6 +13,16 @@ export interface AppSettings {
   remoteControlEnabled: boolean;
   remoteControlPort: number;
   remoteControlToken: string;
+  autoStartBreaks: boolean;
+  autoStartFocus: boolean;
+  autoStartDelaySec: number;
+  autoStopAfterCycles: number;
+  flowModeEnabled: boolean;
+  trashRetentionDays: number;
+  dayStartsAt: string;
+  dailyGoalPomodoros: number;
+  restWeekdays: number[];
+  vacationDates: string[];
 }
 
 export interface AppSettingsPatch {
@@ -25,6 +36,16 @@ export interface AppSettingsPatch {
   remoteControlEnabled?: boolean;
   remoteControlPort?: number;
   remoteControlToken?: string;
+  autoStartBreaks?: boolean;
+  autoStartFocus?: boolean;
+  autoStartDelaySec?: number;
+  autoStopAfterCycles?: number;
+  flowModeEnabled?: boolean;
+  trashRetentionDays?: number;
+  dayStartsAt?: string;
+  dailyGoalPomodoros?: number;
+  restWeekdays?: number[];
+  vacationDates?: string[];
 }
 
 export interface TimerState {
@@ -37,12 +58,100 @@ export interface TimerState {
   interruptions: number;
   currentProjectId: number | null;
   currentTagIds: number[];
+  currentTaskId: number | null;
+  dayPlanItemId: number | null;
   targetEndsAt: number | null;
+  phaseLabel: string;
+  countsAsFocus: boolean;
+  planId: number | null;
+  planStep: number;
+  profileId: number | null;
+  autoStartAt: number | null;
+  autoRunCycles: number;
+  inOvertime: boolean;
+  overtimeSeconds: number;
+  adjustedSeconds: number;
+  pauses: SessionPause[];
+  interruptionLog: InterruptionEntry[];
+  sessionNote: string | null;
+}
+
+export type InterruptionKind = "internal" | "external";
+
+export interface InterruptionReason {
+  kind?: InterruptionKind | null;
+  note?: string | null;
+}
+
+export interface InterruptionEntry {
+  occurredAt: number;
+  kind: InterruptionKind | null;
+  note: string | null;
+  paused: boolean;
+}
+
+export interface InterruptionReasonStat {
+  kind: InterruptionKind | null;
+  note: string | null;
+  pauses: number;
+  distractions: number;
+}
+
+export interface SessionPause {
+  pausedAt: number;
+  resumedAt: number | null;
 }
 
 export interface StartTimerRequest {
   projectId?: number | null;
   tagIds?: number[];
+  note?: string | null;
+  taskId?: number | null;
+}
+
+export type OverlapPolicy = "accept" | "reject" | "trim" | "replace";
+
+export interface CompleteSessionRequest {
+  startedAt: number;
+  endedAt: number;
+  phase: TimerPhase;
+  durationSec: number;
+  completed: boolean;
+  interruptions: number;
+  projectId: number | null;
+  tagIds: number[] | null;
+  countsAsFocus?: boolean;
+  profileId?: number | null;
+  plannedDurationSec?: number;
+  overtimeSec?: number;
+  adjustedSec?: number;
+  note?: string | null;
+  taskId?: number | null;
+  overlap?: OverlapPolicy;
+}
+
+export type SessionErrorCode = "invalid_field" | "not_found" | "overlap" | "database";
+
+/** Rejection payload of `sessionComplete` and `sessionUpdate`. */
+export interface SessionError {
+  code: SessionErrorCode;
+  field: string | null;
+  message: string;
+  conflictingSessionIds: number[];
+}
+
+export interface UpdateSessionRequest {
+  id: number;
+  startedAt?: number;
+  endedAt?: number;
+  phase?: TimerPhase;
+  durationSec?: number;
+  completed?: boolean;
+  countsAsFocus?: boolean;
+  projectId?: number | null;
+  tagIds?: number[];
+  note?: string | null;
+  taskId?: number | null;
 }
 
 export interface SessionRecord {
@@ -55,6 +164,18 @@ export interface SessionRecord {
   interruptions: number;
   projectId: number | null;
   tagIds: number[];
+  countsAsFocus: boolean;
+  profileId: number | null;
+  plannedDurationSec: number;
+  overtimeSec: number;
+  adjustedSec: number;
+  pauses: SessionPause[];
+  interruptionLog: InterruptionEntry[];
+  note: string | null;
+  taskId: number | null;
+  focusRating: number | null;
+  energyLevel: number | null;
+  utcOffsetSec: number | null;
 }
 
 export interface AnalyticsRange {
@@ -62,14 +183,35 @@ export interface AnalyticsRange {
   to?: number;
   projectId?: number;
   tagId?: number;
+  profileId?: number;
+  taskId?: number;
+  includeSubprojects?: boolean;
 }
 
 export interface AnalyticsSummary {
   totalFocusSec: number;
   completedPomodoros: number;
   streakDays: number;
+  longestStreakDays: number;
   interruptions: number;
   avgDailyFocusSec: number;
+  overtimeSec: number;
+  adjustedSessions: number;
+}
+
+export interface StreakStats {
+  currentDays: number;
+  longestDays: number;
+  longestStart: string | null;
+  longestEnd: string | null;
+  todayGoalMet: boolean;
+}
+
+export type TimeseriesGranularity = "day" | "week" | "month" | "year";
+
+export interface TimeseriesOptions {
+  granularity?: TimeseriesGranularity;
+  zeroFill?: boolean;
 }
 
 export interface TimeseriesPoint {
@@ -79,11 +221,60 @@ export interface TimeseriesPoint {
   interruptions: number;
 }
 
+export type BreakdownDimension = "project" | "tag";
+
+export interface BreakdownRow {
+  id: number | null;
+  label: string;
+  focusSeconds: number;
+  completedPomodoros: number;
+  interruptions: number;
+  sessions: number;
+  completionRate: number;
+  focusPercent: number;
+  pomodoroPercent: number;
+}
+
+export interface HeatmapCell {
+  weekday: number;
+  hour: number;
+  focusSeconds: number;
+  completedPomodoros: number;
+}
+
+export interface RatingBucket {
+  key: string;
+  label: string;
+  sessions: number;
+  avgFocusRating: number;
+  avgEnergyLevel: number | null;
+}
+
+export interface RatingCorrelations {
+  ratedSessions: number;
+  byHour: RatingBucket[];
+  byProject: RatingBucket[];
+  byInterruptions: RatingBucket[];
+  byLength: RatingBucket[];
+  interruptionsCorrelation: number | null;
+  lengthCorrelation: number | null;
+}
+
+export interface TagNamespaceStat {
+  tagId: number;
+  name: string;
+  focusSeconds: number;
+  completedPomodoros: number;
+  sessions: number;
+}
+
 export interface Project {
   id: number;
   name: string;
   color?: string | null;
   archived: boolean;
+  parentId: number | null;
+  path: string;
 }
 
 export interface ProjectInput {
@@ -91,16 +282,73 @@ export interface ProjectInput {
   name: string;
   color?: string | null;
   archived?: boolean;
+  parentId?: number | null;
 }
 
 export interface Tag {
   id: number;
   name: string;
+  archived: boolean;
+  parentId: number | null;
 }
 
 export interface TagInput {
   id?: number;
   name: string;
+  archived?: boolean;
+}
+
+export type TaskStatus = "todo" | "in_progress" | "done";
+
+export interface Task {
+  id: number;
+  projectId: number | null;
+  title: string;
+  estimatePomodoros: number | null;
+  status: TaskStatus;
+  position: number;
+  createdAt: number;
+  completedAt: number | null;
+  actualPomodoros: number;
+  focusSeconds: number;
+}
+
+export interface DayPlanItem {
+  id: number;
+  date: string;
+  position: number;
+  taskId: number | null;
+  projectId: number | null;
+  tagIds: number[];
+  title: string | null;
+  plannedPomodoros: number;
+  completedPomodoros: number;
+}
+
+export interface DayPlanItemInput {
+  id?: number;
+  taskId?: number | null;
+  projectId?: number | null;
+  tagIds?: number[];
+  plannedPomodoros: number;
+}
+
+export interface DayPlanReport {
+  date: string;
+  items: DayPlanItem[];
+  plannedPomodoros: number;
+  completedPomodoros: number;
+  focusPomodoros: number;
+  unplannedPomodoros: number;
+  focusSeconds: number;
+}
+
+export interface TaskInput {
+  id?: number;
+  projectId: number | null;
+  title: string;
+  estimatePomodoros: number | null;
+  status?: TaskStatus;
 }
 
 export interface ExportRange {
@@ -118,7 +366,61 @@ export interface ResetAllResult {
   timer: TimerState;
 }
 
+export type TrashKind = "session" | "project" | "tag";
+
+export interface TrashItem {
+  kind: TrashKind;
+  id: number;
+  label: string;
+  startedAt: number | null;
+  deletedAt: number;
+}
+
 export interface PhaseCompletedEvent {
   completedPhase: TimerPhase;
   nextPhase: TimerPhase;
+  completedLabel: string;
+  nextLabel: string;
+  rateSessionId: number | null;
+}
+
+export interface PhaseRecoveredEvent extends PhaseCompletedEvent {
+  session: SessionRecord;
+}
+
+export interface CyclePlanStep {
+  phase: TimerPhase;
+  label: string;
+  durationMin: number;
+  countsAsFocus: boolean;
+}
+
+export interface CyclePlan {
+  id: number;
+  name: string;
+  steps: CyclePlanStep[];
+}
+
+export interface TimerProfile {
+  id: number;
+  name: string;
+  focusMin: number;
+  shortBreakMin: number;
+  longBreakMin: number;
+  longBreakEvery: number;
+}
+
+export interface TimerProfileInput {
+  id?: number;
+  name: string;
+  focusMin: number;
+  shortBreakMin: number;
+  longBreakMin: number;
+  longBreakEvery: number;
+}
+
+export interface CyclePlanInput {
+  id?: number;
+  name: string;
+  steps: CyclePlanStep[];
 }
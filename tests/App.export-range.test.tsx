@@ -17,6 +17,10 @@ const { exportCsvMock, exportJsonMock, baseSettings, baseTimer } = vi.hoisted(
       remoteControlEnabled: false,
       remoteControlPort: 48484,
       remoteControlToken: "token",
+      dayStartsAt: "00:00",
+      dailyGoalPomodoros: 0,
+      restWeekdays: [],
+      vacationDates: [],
     },
     baseTimer: {
       phase: "focus",
@@ -38,6 +42,7 @@ vi.mock("@/api", () => ({
     totalFocusSec: 0,
     completedPomodoros: 0,
     streakDays: 0,
+    longestStreakDays: 0,
     interruptions: 0,
     avgDailyFocusSec: 0,
   }),
@@ -45,8 +50,15 @@ vi.mock("@/api", () => ({
   exportCsv: exportCsvMock,
   exportJson: exportJsonMock,
   projectsList: vi.fn().mockResolvedValue([]),
-  projectsUpsert: vi.fn().mockResolvedValue({ id: 1, name: "x", archived: false }),
+  projectsUpsert: vi.fn().mockResolvedValue({
+    id: 1,
+    name: "x",
+    archived: false,
+    parentId: null,
+    path: "x",
+  }),
   sessionHistory: vi.fn().mockResolvedValue([]),
+  sessionRate: vi.fn().mockResolvedValue(undefined),
   settingsGet: vi.fn().mockResolvedValue(baseSettings),
   settingsUpdate: vi.fn().mockResolvedValue(baseSettings),
   resetAllData: vi.fn().mockResolvedValue({
@@ -54,13 +66,14 @@ vi.mock("@/api", () => ({
     timer: baseTimer,
   }),
   tagsList: vi.fn().mockResolvedValue([]),
-  tagsUpsert: vi.fn().mockResolvedValue({ id: 1, name: "x" }),
+  tagsUpsert: vi.fn().mockResolvedValue({ id: 1, name: "x", archived: false, parentId: null }),
   timerGetState: vi.fn().mockResolvedValue(baseTimer),
   timerPause: vi.fn().mockResolvedValue(baseTimer),
   timerResume: vi.fn().mockResolvedValue(baseTimer),
   timerSkip: vi.fn().mockResolvedValue(baseTimer),
   timerStart: vi.fn().mockResolvedValue(baseTimer),
   timerSetContext: vi.fn().mockResolvedValue(baseTimer),
+  timerTakeRecovered: vi.fn().mockResolvedValue(null),
 }));
 
 vi.mock("@tauri-apps/api/event", () => ({
@@ -52,6 +52,18 @@ describe("StatsChart", () => {
         interruptions: 0,
         projectId: null,
         tagIds: [],
+        countsAsFocus: true,
+        profileId: null,
+        plannedDurationSec: 1800,
+        overtimeSec: 0,
+        adjustedSec: 0,
+        pauses: [],
+        interruptionLog: [],
+        note: null,
+        taskId: null,
+        focusRating: null,
+        energyLevel: null,
+        utcOffsetSec: null,
       },
       {
         id: 2,
@@ -63,6 +75,18 @@ describe("StatsChart", () => {
         interruptions: 0,
         projectId: null,
         tagIds: [],
+        countsAsFocus: false,
+        profileId: null,
+        plannedDurationSec: 300,
+        overtimeSec: 0,
+        adjustedSec: 0,
+        pauses: [],
+        interruptionLog: [],
+        note: null,
+        taskId: null,
+        focusRating: null,
+        energyLevel: null,
+        utcOffsetSec: null,
       },
       {
         id: 3,
@@ -74,6 +98,18 @@ describe("StatsChart", () => {
         interruptions: 0,
         projectId: null,
         tagIds: [],
+        countsAsFocus: true,
+        profileId: null,
+        plannedDurationSec: 900,
+        overtimeSec: 0,
+        adjustedSec: 0,
+        pauses: [],
+        interruptionLog: [],
+        note: null,
+        taskId: null,
+        focusRating: null,
+        energyLevel: null,
+        utcOffsetSec: null,
       },
     ];
 